@@ -0,0 +1,33 @@
+use super::verify_file;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+pub enum Base32SubCommand {
+    #[command(name = "encode", about = "Encode input as RFC 4648 base32")]
+    Encode(Base32EncodeOpts),
+    #[command(name = "decode", about = "Decode a base32 string")]
+    Decode(Base32DecodeOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct Base32EncodeOpts {
+    // default_value_t = "-": input from stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    // leave out the trailing "=" padding
+    #[arg(long)]
+    pub no_pad: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct Base32DecodeOpts {
+    // case, whitespace and "=" padding are ignored
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    // write the result to a file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<String>,
+    // overwrite --output if it already exists
+    #[arg(long)]
+    pub force: bool,
+}
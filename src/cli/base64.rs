@@ -12,11 +12,37 @@ pub enum Base64SubCommand {
 
 #[derive(Debug, Parser)]
 pub struct Base64EncodeOpts {
-    // default_value_t = "-": input from stdin
-    #[arg(short, long, value_parser = verify_file, default_value = "-")]
-    pub input: String,
+    // one or more files concatenated in order, "-" for stdin
+    #[arg(short, long, value_parser = verify_file, num_args = 1.., default_value = "-")]
+    pub input: Vec<String>,
+    // encode this string instead of reading --input
+    #[arg(long, conflicts_with_all = ["input", "stream"])]
+    pub literal: Option<String>,
+    // bytes inserted between consecutive inputs, escapes like \n are not interpreted
+    #[arg(long, default_value = "")]
+    pub separator: String,
     #[arg(short, long, value_parser = parse_base64_format, default_value = "standard")]
     pub format: Base64Format,
+    // write the result to a file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<String>,
+    // overwrite --output if it already exists
+    #[arg(long)]
+    pub force: bool,
+    // insert a newline every N characters, 0 disables wrapping
+    #[arg(long, default_value_t = 0)]
+    pub wrap: usize,
+    // encode in chunks instead of loading the whole input into memory
+    #[arg(long, conflicts_with = "wrap")]
+    pub stream: bool,
+    // encode the loaded input N times and print ops/sec and MB/s on stderr
+    #[arg(
+        long,
+        hide = true,
+        value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with_all = ["literal", "stream"]
+    )]
+    pub bench: Option<u32>,
 }
 
 #[derive(Debug, Parser)]
@@ -24,14 +50,43 @@ pub struct Base64DecodeOpts {
     // default_value_t = "-": input from stdin
     #[arg(short, long, value_parser = verify_file, default_value = "-")]
     pub input: String,
-    #[arg(short, long, value_parser = parse_base64_format, default_value = "standard")]
+    // decode this string instead of reading --input
+    #[arg(long, conflicts_with_all = ["input", "stream"])]
+    pub literal: Option<String>,
+    #[arg(short, long, value_parser = parse_base64_format, default_value = "auto")]
     pub format: Base64Format,
+    // write the result to a file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<String>,
+    // overwrite --output if it already exists
+    #[arg(long)]
+    pub force: bool,
+    // the payload is UTF-8 text, print it as a string with a trailing newline
+    #[arg(long)]
+    pub text: bool,
+    // drop every character outside the base64 alphabet before decoding, like GNU base64 -i
+    #[arg(long, conflicts_with = "stream")]
+    pub ignore_garbage: bool,
+    // decode in chunks instead of loading the whole input into memory
+    #[arg(long, conflicts_with = "text")]
+    pub stream: bool,
+    // decode the loaded input N times and print ops/sec and MB/s on stderr
+    #[arg(
+        long,
+        hide = true,
+        value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with_all = ["literal", "stream"]
+    )]
+    pub bench: Option<u32>,
 }
 
 #[derive(Debug, Clone, Copy)]
 pub enum Base64Format {
     Standard,
     UrlSafe,
+    UrlSafePad,
+    // decode only: try standard first, then url-safe
+    Auto,
 }
 
 fn parse_base64_format(format: &str) -> Result<Base64Format, anyhow::Error> {
@@ -45,6 +100,8 @@ impl FromStr for Base64Format {
         match s {
             "standard" => Ok(Base64Format::Standard),
             "urlsafe" => Ok(Base64Format::UrlSafe),
+            "urlsafe-pad" => Ok(Base64Format::UrlSafePad),
+            "auto" => Ok(Base64Format::Auto),
             v => Err(anyhow::anyhow!("Unsupported format: {}", v)),
         }
     }
@@ -55,6 +112,8 @@ impl From<Base64Format> for &'static str {
         match format {
             Base64Format::Standard => "standard",
             Base64Format::UrlSafe => "urlsafe",
+            Base64Format::UrlSafePad => "urlsafe-pad",
+            Base64Format::Auto => "auto",
         }
     }
 }
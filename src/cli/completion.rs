@@ -0,0 +1,15 @@
+use clap::Parser;
+use clap_complete::Shell;
+
+#[derive(Debug, Parser)]
+pub struct CompletionOpts {
+    // bash, zsh, fish, powershell or elvish
+    #[arg(value_parser = parse_shell)]
+    pub shell: Shell,
+}
+
+fn parse_shell(shell: &str) -> Result<Shell, anyhow::Error> {
+    shell
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Unsupported shell: {}", shell))
+}
@@ -0,0 +1,73 @@
+use super::verify_file;
+use clap::Parser;
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Parser)]
+pub struct CompressOpts {
+    // default_value_t = "-": input from stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    // "-" writes the compressed bytes to stdout
+    #[arg(short, long, default_value = "-")]
+    pub output: String,
+    #[arg(long, value_parser = parse_compress_algo, default_value = "zstd")]
+    pub algo: CompressAlgo,
+    // zstd: 1..=22, gzip: 0..=9, each algorithm's default when omitted
+    #[arg(long)]
+    pub level: Option<i32>,
+    // overwrite --output if it already exists
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct DecompressOpts {
+    // default_value_t = "-": input from stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    // "-" writes the decompressed bytes to stdout
+    #[arg(short, long, default_value = "-")]
+    pub output: String,
+    #[arg(long, value_parser = parse_compress_algo, default_value = "zstd")]
+    pub algo: CompressAlgo,
+    // overwrite --output if it already exists
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompressAlgo {
+    Zstd,
+    Gzip,
+}
+
+fn parse_compress_algo(algo: &str) -> Result<CompressAlgo, anyhow::Error> {
+    algo.parse()
+}
+
+impl FromStr for CompressAlgo {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "zstd" => Ok(CompressAlgo::Zstd),
+            "gzip" => Ok(CompressAlgo::Gzip),
+            v => Err(anyhow::anyhow!("Unsupported algorithm: {}", v)),
+        }
+    }
+}
+
+impl From<CompressAlgo> for &'static str {
+    fn from(algo: CompressAlgo) -> Self {
+        match algo {
+            CompressAlgo::Zstd => "zstd",
+            CompressAlgo::Gzip => "gzip",
+        }
+    }
+}
+
+impl fmt::Display for CompressAlgo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
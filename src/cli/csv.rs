@@ -1,40 +1,254 @@
-use super::verify_file;
+use super::{verify_file, verify_path};
+use crate::is_url;
 use clap::Parser;
-use std::{fmt, str::FromStr};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub enum OutputFormat {
     Json,
     Yaml,
+    Toml,
+    Ndjson,
+    Xml,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Delimiter {
+    Auto,
+    Char(u8),
 }
 
 #[derive(Debug, Parser)]
 pub struct CsvOpts {
-    #[arg(short, long, value_parser = verify_file)]
+    // a file, "-" for stdin, or an http(s):// URL (needs the `url` feature)
+    #[arg(short, long, value_parser = verify_input)]
     pub input: String,
 
     #[arg(short, long)]
     pub output: Option<String>,
 
-    #[arg(long, value_parser = parse_format, default_value = "json")]
-    pub format: OutputFormat,
+    // write to <dir>/<input-stem>.<format>, -o takes precedence
+    #[arg(long, value_parser = verify_path)]
+    pub output_dir: Option<PathBuf>,
+
+    // write each row to its own <output-dir>/row-0001.json, row-0002.json, ...
+    #[arg(
+        long,
+        requires = "output_dir",
+        conflicts_with_all = ["output", "raw_headers", "validate", "stats"]
+    )]
+    pub split: bool,
+
+    // name the --split files after this column, whose values must be unique
+    #[arg(long, requires = "split")]
+    pub name_column: Option<String>,
+
+    /// Output format: json, yaml, toml, ndjson or xml. Cells are always kept as strings,
+    /// so numeric-looking values like "007" are not coerced. Defaults to the -o file
+    /// extension, or json.
+    #[arg(long, value_parser = parse_format)]
+    pub format: Option<OutputFormat>,
+
+    // pretty-print json output, already the default; not allowed with ndjson
+    #[arg(long)]
+    pub pretty: bool,
+
+    // write json output with one record per line instead of pretty-printed
+    #[arg(long, conflicts_with = "pretty")]
+    pub compact: bool,
+
+    // a single ASCII character, or "auto" to sniff it from the first line
+    #[arg(short, long, value_parser = parse_delimiter, default_value = ",")]
+    pub delimiter: Delimiter,
+
+    // a single ASCII character that quotes fields, e.g. '`' for backtick-quoted files
+    #[arg(long, value_parser = parse_quote, default_value = "\"")]
+    pub quote: u8,
+
+    // read quote characters as ordinary data
+    #[arg(long, conflicts_with = "quote")]
+    pub no_quoting: bool,
+
+    // a single ASCII character that escapes quotes inside quoted fields, e.g. '\'
+    #[arg(long, value_parser = parse_escape)]
+    pub escape: Option<u8>,
+
+    // only output these columns, in the given order
+    #[arg(long, value_delimiter = ',')]
+    pub columns: Vec<String>,
+
+    // one column name per line, added after any --columns
+    #[arg(long, value_parser = verify_file)]
+    pub columns_file: Option<String>,
+
+    // only keep rows where column == value, repeat to AND several filters
+    #[arg(long, value_parser = parse_filter)]
+    pub filter: Vec<(String, String)>,
+
+    // cells equal to this token become null (left out of toml), repeat for several tokens
+    #[arg(long)]
+    pub null_value: Vec<String>,
+
+    // treat the first row as data, columns are named col1, col2, ...
+    #[arg(long)]
+    pub no_header: bool,
+
+    // emit a headers array and one array per row, keeping duplicate column names (json/ndjson)
+    #[arg(long)]
+    pub raw_headers: bool,
+
+    // strip leading/trailing whitespace from headers and cells
+    #[arg(long)]
+    pub trim: bool,
+
+    // only report rows whose field count differs from the header, no output is written
+    #[arg(long)]
+    pub validate: bool,
+
+    // print the row count, column names and non-empty cells per column as JSON, no output is written
+    #[arg(long, conflicts_with = "validate")]
+    pub stats: bool,
+
+    // base64-encode the whole converted output, e.g. to embed it in an env var
+    #[arg(long, conflicts_with_all = ["split", "validate", "stats"])]
+    pub base64: bool,
+
+    // don't print the conversion summary on stderr
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    // skip the first N data rows
+    #[arg(long, default_value_t = 0)]
+    pub skip: usize,
+
+    // stop after N data rows
+    #[arg(long)]
+    pub limit: Option<usize>,
+
+    // show a progress bar on stderr, a spinner when reading from stdin
+    #[arg(long)]
+    pub progress: bool,
+
+    // seconds to wait for a URL input before giving up
+    #[arg(long, default_value_t = 30)]
+    pub timeout: u64,
+
+    // overwrite the output file if it already exists
+    #[arg(long)]
+    pub force: bool,
+}
+
+impl CsvOpts {
+    // an explicit --format wins, then the -o extension, then json
+    pub fn output_format(&self) -> Result<OutputFormat, anyhow::Error> {
+        if let Some(format) = self.format {
+            return Ok(format);
+        }
+        let ext = self
+            .output
+            .as_deref()
+            .and_then(|output| Path::new(output).extension())
+            .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+        match ext.as_deref() {
+            None => Ok(OutputFormat::Json),
+            Some("yml") => Ok(OutputFormat::Yaml),
+            Some("jsonl") => Ok(OutputFormat::Ndjson),
+            Some(ext) => ext.parse().map_err(|_| {
+                anyhow::anyhow!(
+                    "cannot infer the output format from .{}, pass --format",
+                    ext
+                )
+            }),
+        }
+    }
+
+    pub fn output_path(&self) -> String {
+        if let Some(output) = &self.output {
+            return output.clone();
+        }
+
+        match &self.output_dir {
+            Some(dir) => {
+                let name = format!("{}.{}", self.input_stem(), self.default_format());
+                dir.join(name).display().to_string()
+            }
+            None => format!("output.{}", self.default_format()),
+        }
+    }
+
+    // without -o there's no extension to infer from
+    fn default_format(&self) -> OutputFormat {
+        self.format.unwrap_or(OutputFormat::Json)
+    }
 
-    #[arg(short, long, default_value_t = ',')]
-    pub delimiter: char,
+    // "data.csv" / "data.csv.gz" -> "data", stdin -> "output"
+    fn input_stem(&self) -> &str {
+        let mut path = Path::new(&self.input);
+        if path.extension().is_some_and(|ext| ext == "gz") {
+            path = Path::new(path.file_stem().unwrap_or_default());
+        }
+        match path.file_stem().and_then(|s| s.to_str()) {
+            Some(stem) if self.input != "-" => stem,
+            _ => "output",
+        }
+    }
+}
 
-    #[arg(long, default_value_t = true)]
-    pub header: bool,
+fn verify_input(input: &str) -> Result<String, &'static str> {
+    if is_url(input) {
+        Ok(input.into())
+    } else {
+        verify_file(input)
+    }
 }
 
 fn parse_format(format: &str) -> Result<OutputFormat, anyhow::Error> {
     format.parse()
 }
 
+fn parse_delimiter(delimiter: &str) -> Result<Delimiter, &'static str> {
+    match delimiter {
+        "auto" => Ok(Delimiter::Auto),
+        _ => single_ascii(delimiter)
+            .map(Delimiter::Char)
+            .ok_or("delimiter must be a single ASCII character or \"auto\""),
+    }
+}
+
+fn parse_quote(quote: &str) -> Result<u8, &'static str> {
+    single_ascii(quote).ok_or("quote must be a single ASCII character")
+}
+
+fn parse_escape(escape: &str) -> Result<u8, &'static str> {
+    single_ascii(escape).ok_or("escape must be a single ASCII character")
+}
+
+fn single_ascii(s: &str) -> Option<u8> {
+    match s.as_bytes() {
+        [b] if b.is_ascii() => Some(*b),
+        _ => None,
+    }
+}
+
+fn parse_filter(filter: &str) -> Result<(String, String), &'static str> {
+    match filter.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.into(), value.into())),
+        _ => Err("filter must be in the form key=value"),
+    }
+}
+
 impl From<OutputFormat> for &'static str {
     fn from(format: OutputFormat) -> Self {
         match format {
             OutputFormat::Json => "json",
             OutputFormat::Yaml => "yaml",
+            OutputFormat::Toml => "toml",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Xml => "xml",
         }
     }
 }
@@ -46,6 +260,9 @@ impl FromStr for OutputFormat {
         match s {
             "json" => Ok(OutputFormat::Json),
             "yaml" => Ok(OutputFormat::Yaml),
+            "toml" => Ok(OutputFormat::Toml),
+            "ndjson" => Ok(OutputFormat::Ndjson),
+            "xml" => Ok(OutputFormat::Xml),
             v => Err(anyhow::anyhow!("Unsupported format: {}", v)),
         }
     }
@@ -56,3 +273,138 @@ impl fmt::Display for OutputFormat {
         write!(f, "{}", Into::<&str>::into(*self))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_delimiter() {
+        assert_eq!(parse_delimiter(","), Ok(Delimiter::Char(b',')));
+        assert_eq!(parse_delimiter(";"), Ok(Delimiter::Char(b';')));
+        assert_eq!(parse_delimiter("\t"), Ok(Delimiter::Char(b'\t')));
+        assert_eq!(parse_delimiter("auto"), Ok(Delimiter::Auto));
+        assert!(parse_delimiter("").is_err());
+        assert!(parse_delimiter(";;").is_err());
+        assert!(parse_delimiter("é").is_err());
+    }
+
+    #[test]
+    fn test_parse_quote() {
+        assert_eq!(parse_quote("\""), Ok(b'"'));
+        assert_eq!(parse_quote("`"), Ok(b'`'));
+        assert!(parse_quote("").is_err());
+        assert!(parse_quote("''").is_err());
+        assert!(parse_quote("´").is_err());
+        assert_eq!(parse_escape("\\"), Ok(b'\\'));
+        assert!(parse_escape("\\\\").is_err());
+    }
+
+    #[test]
+    fn test_parse_filter() {
+        assert_eq!(
+            parse_filter("status=active"),
+            Ok(("status".into(), "active".into()))
+        );
+        assert_eq!(parse_filter("note=a=b"), Ok(("note".into(), "a=b".into())));
+        assert_eq!(parse_filter("status="), Ok(("status".into(), "".into())));
+        assert!(parse_filter("status").is_err());
+        assert!(parse_filter("=active").is_err());
+    }
+
+    #[test]
+    fn test_output_path() {
+        let opts = CsvOpts::parse_from(["csv", "-i", "fixtures/players.csv"]);
+        assert_eq!(opts.output_path(), "output.json");
+
+        let opts = CsvOpts::parse_from(["csv", "-i", "fixtures/players.csv", "--format", "yaml"]);
+        assert_eq!(opts.output_path(), "output.yaml");
+
+        let opts = CsvOpts::parse_from([
+            "csv",
+            "-i",
+            "fixtures/players.csv",
+            "--output-dir",
+            "fixtures",
+            "--format",
+            "toml",
+        ]);
+        assert_eq!(opts.output_path(), "fixtures/players.toml");
+
+        let opts = CsvOpts::parse_from([
+            "csv",
+            "-i",
+            "fixtures/players.csv.gz",
+            "--output-dir",
+            "fixtures",
+        ]);
+        assert_eq!(opts.output_path(), "fixtures/players.json");
+
+        let opts = CsvOpts::parse_from([
+            "csv",
+            "-i",
+            "fixtures/players.csv",
+            "--output-dir",
+            "fixtures",
+            "-o",
+            "out.json",
+        ]);
+        assert_eq!(opts.output_path(), "out.json");
+
+        let opts = CsvOpts::parse_from(["csv", "-i", "-", "--output-dir", "fixtures"]);
+        assert_eq!(opts.output_path(), "fixtures/output.json");
+    }
+
+    #[test]
+    fn test_output_format() -> anyhow::Result<()> {
+        let format = |args: &[&str]| {
+            CsvOpts::parse_from([&["csv", "-i", "fixtures/players.csv"], args].concat())
+                .output_format()
+        };
+        assert!(matches!(format(&[])?, OutputFormat::Json));
+        assert!(matches!(format(&["-o", "out.json"])?, OutputFormat::Json));
+        assert!(matches!(format(&["-o", "out.yaml"])?, OutputFormat::Yaml));
+        assert!(matches!(
+            format(&["-o", "dir/OUT.YML"])?,
+            OutputFormat::Yaml
+        ));
+        assert!(matches!(format(&["-o", "-"])?, OutputFormat::Json));
+        // an explicit --format wins over the extension
+        assert!(matches!(
+            format(&["-o", "out.yaml", "--format", "toml"])?,
+            OutputFormat::Toml
+        ));
+        assert!(matches!(
+            format(&["-o", "out.txt", "--format", "ndjson"])?,
+            OutputFormat::Ndjson
+        ));
+
+        let err = format(&["-o", "out.txt"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "cannot infer the output format from .txt, pass --format"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_input() {
+        assert!(verify_input("fixtures/players.csv").is_ok());
+        assert!(verify_input("-").is_ok());
+        assert!(verify_input("https://example.com/data.csv").is_ok());
+        assert!(verify_input("http://localhost:8080/data.csv").is_ok());
+        assert!(verify_input("ftp://example.com/data.csv").is_err());
+    }
+
+    #[test]
+    fn test_output_dir_must_exist() {
+        let ret = CsvOpts::try_parse_from([
+            "csv",
+            "-i",
+            "fixtures/players.csv",
+            "--output-dir",
+            "not-exist",
+        ]);
+        assert!(ret.is_err());
+    }
+}
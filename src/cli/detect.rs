@@ -0,0 +1,9 @@
+use super::verify_file;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+pub struct DetectOpts {
+    // default_value_t = "-": input from stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+}
@@ -1,16 +1,122 @@
-use clap::Parser;
+use clap::{ArgAction, Parser};
+use std::{fmt, str::FromStr};
 
 #[derive(Debug, Parser)]
 pub struct GenPassOpts {
     #[arg(short, long, default_value_t = 16)]
     pub length: u8,
-    // 0 for not supported, 1 for supported
+    // number of passwords to generate, one per line
+    #[arg(short, long, default_value_t = 1)]
+    pub count: usize,
+    // all classes are enabled by default, --no-<class> turns one off
+    #[arg(long = "no-uppercase", action = ArgAction::SetFalse)]
+    pub uppercase: bool,
+    #[arg(long = "no-lowercase", action = ArgAction::SetFalse)]
+    pub lowercase: bool,
+    #[arg(long = "no-number", action = ArgAction::SetFalse)]
+    pub number: bool,
+    #[arg(long = "no-symbol", action = ArgAction::SetFalse)]
+    pub symbol: bool,
+    // minimum number of characters from each enabled class
     #[arg(long, default_value_t = 1)]
-    pub uppercase: u8,
+    pub min_uppercase: u8,
     #[arg(long, default_value_t = 1)]
-    pub lowercase: u8,
+    pub min_lowercase: u8,
     #[arg(long, default_value_t = 1)]
-    pub number: u8,
+    pub min_number: u8,
     #[arg(long, default_value_t = 1)]
-    pub symbol: u8,
+    pub min_symbol: u8,
+    // replace the default symbol set !@#$%^&*_
+    #[arg(long)]
+    pub symbols: Option<String>,
+    // look-alike characters (I, l, O, 0) are left out unless this is given
+    #[arg(long = "no-exclude-ambiguous", action = ArgAction::SetFalse)]
+    pub exclude_ambiguous: bool,
+    // regenerate until the zxcvbn score is at least N (0-4), giving up after 100 tries
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=4))]
+    pub min_score: u8,
+    // reproducible output for testing, never use it for real passwords
+    #[arg(long)]
+    pub seed: Option<u64>,
+    // print score, guesses and crack times as JSON on stderr
+    #[arg(long)]
+    pub stats_json: bool,
+    // generate a passphrase of random words instead of a password
+    #[arg(long)]
+    pub passphrase: bool,
+    /// Number of words in a --passphrase. Each word from the 474-word list adds about 8.9 bits,
+    /// the default of 8 gives about 71 bits.
+    #[arg(long, default_value_t = 8)]
+    pub words: usize,
+    #[arg(long, default_value = "-")]
+    pub separator: String,
+    // write passwords to a file instead of stdout, strength estimates stay on stderr
+    #[arg(short, long)]
+    pub output: Option<String>,
+    // add to the end of --output instead of overwriting it
+    #[arg(long, requires = "output")]
+    pub append: bool,
+    // overwrite --output if it already exists
+    #[arg(long, requires = "output", conflicts_with = "append")]
+    pub force: bool,
+    // raw prints a password; base64 or hex print --length random bytes as key material
+    #[arg(long, value_parser = parse_encoding, default_value = "raw", conflicts_with = "passphrase")]
+    pub encoding: GenPassEncoding,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GenPassEncoding {
+    Raw,
+    Base64,
+    Hex,
+}
+
+fn parse_encoding(encoding: &str) -> Result<GenPassEncoding, anyhow::Error> {
+    encoding.parse()
+}
+
+impl FromStr for GenPassEncoding {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "raw" => Ok(GenPassEncoding::Raw),
+            "base64" => Ok(GenPassEncoding::Base64),
+            "hex" => Ok(GenPassEncoding::Hex),
+            v => Err(anyhow::anyhow!("Unsupported encoding: {}", v)),
+        }
+    }
+}
+
+impl From<GenPassEncoding> for &'static str {
+    fn from(encoding: GenPassEncoding) -> Self {
+        match encoding {
+            GenPassEncoding::Raw => "raw",
+            GenPassEncoding::Base64 => "base64",
+            GenPassEncoding::Hex => "hex",
+        }
+    }
+}
+
+impl fmt::Display for GenPassEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_genpass_class_flags() {
+        let opts = GenPassOpts::parse_from(["genpass"]);
+        assert!(opts.uppercase && opts.lowercase && opts.number && opts.symbol);
+
+        let opts = GenPassOpts::parse_from(["genpass", "--no-uppercase", "--no-symbol"]);
+        assert!(!opts.uppercase && !opts.symbol);
+        assert!(opts.lowercase && opts.number);
+
+        assert!(GenPassOpts::try_parse_from(["genpass", "--uppercase", "5"]).is_err());
+    }
 }
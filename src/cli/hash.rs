@@ -0,0 +1,71 @@
+use super::verify_file;
+use clap::Parser;
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Parser)]
+pub struct HashOpts {
+    // default_value_t = "-": input from stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    #[arg(long, value_parser = parse_algo, default_value = "sha256")]
+    pub algo: HashAlgo,
+    // compare against this hex digest instead of printing it, exit non-zero on mismatch
+    #[arg(long)]
+    pub check: Option<String>,
+    // print A-F instead of a-f
+    #[arg(short, long)]
+    pub uppercase: bool,
+    // split the digest into groups of N hex characters, e.g. --group 2 --separator :
+    #[arg(long, default_value_t = 0)]
+    pub group: usize,
+    #[arg(long, default_value = " ")]
+    pub separator: String,
+    // hash the loaded input N times and print ops/sec and MB/s on stderr
+    #[arg(
+        long,
+        hide = true,
+        value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with = "check"
+    )]
+    pub bench: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum HashAlgo {
+    Sha256,
+    Sha512,
+    Blake3,
+}
+
+fn parse_algo(algo: &str) -> Result<HashAlgo, anyhow::Error> {
+    algo.parse()
+}
+
+impl FromStr for HashAlgo {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sha256" => Ok(HashAlgo::Sha256),
+            "sha512" => Ok(HashAlgo::Sha512),
+            "blake3" => Ok(HashAlgo::Blake3),
+            v => Err(anyhow::anyhow!("Unsupported algorithm: {}", v)),
+        }
+    }
+}
+
+impl From<HashAlgo> for &'static str {
+    fn from(algo: HashAlgo) -> Self {
+        match algo {
+            HashAlgo::Sha256 => "sha256",
+            HashAlgo::Sha512 => "sha512",
+            HashAlgo::Blake3 => "blake3",
+        }
+    }
+}
+
+impl fmt::Display for HashAlgo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
@@ -0,0 +1,33 @@
+use super::verify_file;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+pub enum HexSubCommand {
+    #[command(name = "encode", about = "Encode input as hex")]
+    Encode(HexEncodeOpts),
+    #[command(name = "decode", about = "Decode a hex string")]
+    Decode(HexDecodeOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct HexEncodeOpts {
+    // default_value_t = "-": input from stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    // use A-F instead of a-f
+    #[arg(short, long)]
+    pub upper: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct HexDecodeOpts {
+    // whitespace and 0x prefixes are ignored
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    // write the result to a file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<String>,
+    // overwrite --output if it already exists
+    #[arg(long)]
+    pub force: bool,
+}
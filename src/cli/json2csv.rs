@@ -0,0 +1,17 @@
+use super::verify_file;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+pub struct Json2CsvOpts {
+    // newline-delimited JSON objects, "-" for stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+
+    // "-" for stdout
+    #[arg(short, long, default_value = "output.csv")]
+    pub output: String,
+
+    // overwrite the output file if it already exists
+    #[arg(long)]
+    pub force: bool,
+}
@@ -0,0 +1,80 @@
+use super::{parse_duration, verify_file};
+use clap::Parser;
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Parser)]
+pub enum JwtSubCommand {
+    #[command(about = "Sign a JWT with an HMAC or Ed25519 key")]
+    Sign(JwtSignOpts),
+    #[command(about = "Verify a JWT's signature and expiry")]
+    Verify(JwtVerifyOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct JwtSignOpts {
+    // hmac-sha256 key for HS256, ed25519.sk for EdDSA
+    #[arg(short, long, value_parser = verify_file)]
+    pub key: String,
+    #[arg(long, value_parser = parse_alg, default_value = "hs256")]
+    pub alg: JwtAlg,
+    #[arg(long)]
+    pub sub: Option<String>,
+    #[arg(long)]
+    pub aud: Option<String>,
+    // lifetime of the token, e.g. 30s, 15m, 1h or 7d; a bare number is seconds
+    #[arg(long, value_parser = parse_duration, default_value = "1h")]
+    pub exp: u64,
+}
+
+#[derive(Debug, Parser)]
+pub struct JwtVerifyOpts {
+    #[arg(short, long)]
+    pub token: String,
+    // hmac-sha256 key for HS256, ed25519.pk for EdDSA
+    #[arg(short, long, value_parser = verify_file)]
+    pub key: String,
+    // required, with a default an ed25519.pk would be accepted as an HS256 secret
+    #[arg(long, value_parser = parse_alg)]
+    pub alg: JwtAlg,
+    // also require the aud claim to match
+    #[arg(long)]
+    pub aud: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JwtAlg {
+    Hs256,
+    EdDsa,
+}
+
+fn parse_alg(alg: &str) -> Result<JwtAlg, anyhow::Error> {
+    alg.parse()
+}
+
+impl FromStr for JwtAlg {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "hs256" => Ok(JwtAlg::Hs256),
+            "eddsa" => Ok(JwtAlg::EdDsa),
+            _ => Err(anyhow::anyhow!("Unsupported algorithm: {}", s)),
+        }
+    }
+}
+
+// the "alg" header value from RFC 7518 / RFC 8037
+impl From<JwtAlg> for &'static str {
+    fn from(alg: JwtAlg) -> Self {
+        match alg {
+            JwtAlg::Hs256 => "HS256",
+            JwtAlg::EdDsa => "EdDSA",
+        }
+    }
+}
+
+impl fmt::Display for JwtAlg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
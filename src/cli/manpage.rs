@@ -0,0 +1,10 @@
+use super::verify_path;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+pub struct ManpageOpts {
+    // one <command>.1 file is written per (sub)command
+    #[arg(long, value_parser = verify_path)]
+    pub out_dir: PathBuf,
+}
@@ -1,17 +1,42 @@
+mod base32;
 mod base64;
+mod completion;
+mod compress;
 mod csv;
+mod detect;
 mod genpass;
+mod hash;
+mod hex;
+mod json2csv;
+mod jwt;
+mod manpage;
+mod otp;
 mod text;
+mod uuid;
 
-use std::path::{Path, PathBuf};
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
 
 // yzr：此处使用self::csv, 是为了避免与外部Cargo.toml的csv crate模块冲突
+use self::json2csv::Json2CsvOpts;
 pub use self::{
+    base32::Base32SubCommand,
     base64::{Base64Format, Base64SubCommand},
-    csv::OutputFormat,
-    text::{TextSignFormat, TextSubCommand},
+    completion::CompletionOpts,
+    compress::{CompressAlgo, CompressOpts, DecompressOpts},
+    csv::{CsvOpts, Delimiter, OutputFormat},
+    detect::DetectOpts,
+    genpass::{GenPassEncoding, GenPassOpts},
+    hash::{HashAlgo, HashOpts},
+    hex::HexSubCommand,
+    jwt::{JwtAlg, JwtSubCommand},
+    manpage::ManpageOpts,
+    otp::{OtpAlgo, OtpSubCommand},
+    text::{TextCipherFormat, TextSignFormat, TextSubCommand},
+    uuid::{UuidOpts, UuidVersion},
 };
-use self::{csv::CsvOpts, genpass::GenPassOpts};
 use clap::Parser;
 
 // rcli csv -i input -o output.json --header -d ','
@@ -26,19 +51,55 @@ pub struct Opts {
 pub enum SubCommand {
     #[command(name = "csv", about = "Show CSV, or convert CSV to other formats")]
     Csv(CsvOpts),
+    #[command(name = "json2csv", about = "Convert newline-delimited JSON to CSV")]
+    Json2Csv(Json2CsvOpts),
     #[command(name = "genpass", about = "Generate a random password")]
     GenPass(GenPassOpts),
+    #[command(name = "uuid", about = "Generate version 4 or 7 UUIDs")]
+    Uuid(UuidOpts),
+    #[command(name = "hash", about = "Print or check the digest of a file")]
+    Hash(HashOpts),
+    #[command(
+        name = "detect",
+        about = "Guess whether input is json, csv, base64, text or binary"
+    )]
+    Detect(DetectOpts),
+    #[command(name = "compress", about = "Compress a file with zstd or gzip")]
+    Compress(CompressOpts),
+    #[command(name = "decompress", about = "Decompress a zstd or gzip file")]
+    Decompress(DecompressOpts),
+    #[command(subcommand)]
+    Base32(Base32SubCommand),
     #[command(subcommand)]
     Base64(Base64SubCommand),
     #[command(subcommand)]
+    Hex(HexSubCommand),
+    #[command(subcommand)]
     Text(TextSubCommand),
+    #[command(subcommand)]
+    Jwt(JwtSubCommand),
+    #[command(subcommand)]
+    Otp(OtpSubCommand),
+    #[command(name = "completion", about = "Print a shell completion script")]
+    Completion(CompletionOpts),
+    #[command(
+        name = "manpage",
+        about = "Write roff man pages for rcli and its subcommands"
+    )]
+    Manpage(ManpageOpts),
 }
 
 fn verify_file(filename: &str) -> Result<String, &'static str> {
-    if filename == "-" || Path::new(filename).exists() {
-        Ok(filename.into())
-    } else {
-        Err("file does not exist")
+    if filename == "-" {
+        return Ok(filename.into());
+    }
+    if !Path::new(filename).exists() {
+        return Err("file does not exist");
+    }
+    // opening catches permission errors up front instead of at the first read
+    match File::open(filename) {
+        Ok(_) => Ok(filename.into()),
+        Err(_) => Err("file exists but cannot be read"),
     }
 }
 
@@ -51,10 +112,62 @@ fn verify_path(path: &str) -> Result<PathBuf, &'static str> {
     }
 }
 
+// verify_path, but with `mkdir` a missing directory is created first, parents included
+fn verify_or_create_path(path: &Path, mkdir: bool) -> Result<PathBuf, &'static str> {
+    if mkdir && !path.exists() {
+        fs::create_dir_all(path).map_err(|_| "cannot create the directory")?;
+    }
+    verify_path(&path.to_string_lossy())
+}
+
+// e.g. 30s, 15m, 1h or 7d; a bare number is seconds
+fn parse_duration(duration: &str) -> Result<u64, &'static str> {
+    let (value, unit) = match duration.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => duration.split_at(i),
+        None => (duration, "s"),
+    };
+    let value: u64 = value.parse().map_err(|_| "invalid duration")?;
+    let secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err("duration unit must be one of s, m, h or d"),
+    };
+    value.checked_mul(secs).ok_or("duration is too large")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(90));
+        assert_eq!(parse_duration("30s"), Ok(30));
+        assert_eq!(parse_duration("15m"), Ok(900));
+        assert_eq!(parse_duration("1h"), Ok(3600));
+        assert_eq!(parse_duration("7d"), Ok(604800));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("1w").is_err());
+    }
+
+    #[test]
+    fn test_verify_or_create_path() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let nested = dir.path().join("keys/ed25519");
+        assert!(verify_or_create_path(&nested, false).is_err());
+        assert!(!nested.exists());
+        assert_eq!(verify_or_create_path(&nested, true), Ok(nested.clone()));
+        assert!(nested.is_dir());
+        // a file in the way is still rejected
+        let file = dir.path().join("file");
+        fs::write(&file, "")?;
+        assert!(verify_or_create_path(&file, true).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_verify_input_file() {
         assert_eq!(verify_file("-"), Ok("-".into()));
@@ -62,4 +175,25 @@ mod tests {
         assert_eq!(verify_file("Cargo.toml"), Ok("Cargo.toml".into()));
         assert_eq!(verify_file("not-exist"), Err("file does not exist"));
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_verify_unreadable_file() -> anyhow::Result<()> {
+        use std::{fs, os::unix::fs::PermissionsExt};
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("secret.key");
+        fs::write(&path, "secret")?;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o000))?;
+
+        // root ignores file permissions, nothing to check there
+        if File::open(&path).is_ok() {
+            return Ok(());
+        }
+        assert_eq!(
+            verify_file(path.to_str().unwrap()),
+            Err("file exists but cannot be read")
+        );
+        Ok(())
+    }
 }
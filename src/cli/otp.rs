@@ -0,0 +1,79 @@
+use super::verify_file;
+use clap::Parser;
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Parser)]
+pub enum OtpSubCommand {
+    #[command(about = "Print the current TOTP code for a base32 secret")]
+    Generate(OtpGenerateOpts),
+    #[command(about = "Check a TOTP code, allowing one period of clock drift")]
+    Verify(OtpVerifyOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct OtpSecretOpts {
+    // base32 secret, as shown by most authenticator setups
+    #[arg(long, required_unless_present = "secret_file")]
+    pub secret: Option<String>,
+    // read the base32 secret from a file instead, "-" for stdin
+    #[arg(long, value_parser = verify_file, conflicts_with = "secret")]
+    pub secret_file: Option<String>,
+    #[arg(long, value_parser = clap::value_parser!(u32).range(6..=8), default_value_t = 6)]
+    pub digits: u32,
+    // seconds per code
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..), default_value_t = 30)]
+    pub period: u64,
+    #[arg(long, value_parser = parse_algo, default_value = "sha1")]
+    pub algo: OtpAlgo,
+}
+
+#[derive(Debug, Parser)]
+pub struct OtpGenerateOpts {
+    #[command(flatten)]
+    pub secret: OtpSecretOpts,
+}
+
+#[derive(Debug, Parser)]
+pub struct OtpVerifyOpts {
+    #[command(flatten)]
+    pub secret: OtpSecretOpts,
+    #[arg(long)]
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum OtpAlgo {
+    Sha1,
+    Sha256,
+}
+
+fn parse_algo(algo: &str) -> Result<OtpAlgo, anyhow::Error> {
+    algo.parse()
+}
+
+impl FromStr for OtpAlgo {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sha1" => Ok(OtpAlgo::Sha1),
+            "sha256" => Ok(OtpAlgo::Sha256),
+            v => Err(anyhow::anyhow!("Unsupported algorithm: {}", v)),
+        }
+    }
+}
+
+impl From<OtpAlgo> for &'static str {
+    fn from(algo: OtpAlgo) -> Self {
+        match algo {
+            OtpAlgo::Sha1 => "sha1",
+            OtpAlgo::Sha256 => "sha256",
+        }
+    }
+}
+
+impl fmt::Display for OtpAlgo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
@@ -1,4 +1,4 @@
-use super::{verify_file, verify_path};
+use super::{parse_duration, verify_file, verify_or_create_path};
 use clap::Parser;
 use std::{fmt, path::PathBuf, str::FromStr};
 
@@ -10,6 +10,12 @@ pub enum TextSubCommand {
     Verify(TextVerifyOpts),
     #[command(about = "Generate a new key")]
     Generate(TextKeyGenOpts),
+    #[command(about = "Derive the Ed25519 public key from a secret key")]
+    RecoverPubkey(TextRecoverPubkeyOpts),
+    #[command(about = "Encrypt a message with ChaCha20-Poly1305 or AES-256-GCM")]
+    Encrypt(TextEncryptOpts),
+    #[command(about = "Decrypt a message encrypted with text encrypt")]
+    Decrypt(TextDecryptOpts),
 }
 
 #[derive(Debug, Parser)]
@@ -17,10 +23,39 @@ pub struct TextSignOpts {
     // default_value_t = "-": input from stdin
     #[arg(short, long, value_parser = verify_file, default_value = "-")]
     pub input: String,
-    #[arg(short, long, value_parser = verify_file)]
-    pub key: String,
+    #[arg(short, long, value_parser = verify_file, required_unless_present = "passphrase")]
+    pub key: Option<String>,
+    // derive a symmetric key with Argon2id instead of reading --key
+    #[arg(long, conflicts_with_all = ["key", "files"], requires = "salt_source")]
+    pub passphrase: Option<String>,
+    #[arg(long, group = "salt_source")]
+    pub salt: Option<String>,
+    // read the salt bytes from a file instead of --salt
+    #[arg(long, value_parser = verify_file, group = "salt_source")]
+    pub salt_file: Option<String>,
     #[arg(long, value_parser = parse_format, default_value = "blake3")]
     pub format: TextSignFormat,
+    // print the bare base64 signature without the "<format>:" prefix older versions can't read
+    #[arg(long)]
+    pub untagged: bool,
+    // sign "<expiry>.<signature>" valid for this long, e.g. 300, 5m or 1h; verify rejects it afterwards
+    #[arg(long, value_parser = parse_duration)]
+    pub ttl: Option<u64>,
+    // write the signature to a detached file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    // sign the files listed in this file, one path per line, as one concatenated message
+    #[arg(long, value_parser = verify_file, conflicts_with_all = ["input", "files", "passphrase"])]
+    pub manifest: Option<String>,
+    // sign each file and write <file>.sig next to it
+    #[arg(value_parser = verify_file, conflicts_with_all = ["input", "output"])]
+    pub files: Vec<String>,
+    // overwrite an existing --output or <file>.sig files
+    #[arg(long)]
+    pub force: bool,
+    // sign every line of --input on its own and print "<line>\t<signature>" per line
+    #[arg(long, conflicts_with_all = ["files", "manifest", "passphrase"])]
+    pub lines: bool,
 }
 
 #[derive(Debug, Parser)]
@@ -28,30 +63,126 @@ pub struct TextVerifyOpts {
     // default_value_t = "-": input from stdin
     #[arg(short, long, value_parser = verify_file, default_value = "-")]
     pub input: String,
-    #[arg(short, long, value_parser = verify_file)]
-    pub key: String,
+    #[arg(short, long, value_parser = verify_file, required_unless_present = "passphrase")]
+    pub key: Option<String>,
+    // derive a symmetric key with Argon2id instead of reading --key
+    #[arg(long, conflicts_with_all = ["key", "files"], requires = "salt_source")]
+    pub passphrase: Option<String>,
+    #[arg(long, group = "salt_source")]
+    pub salt: Option<String>,
+    // read the salt bytes from a file instead of --salt
+    #[arg(long, value_parser = verify_file, group = "salt_source")]
+    pub salt_file: Option<String>,
+    #[arg(short, long, required_unless_present_any = ["sig_file", "files", "lines"])]
+    pub sig: Option<String>,
+    // read the signature from a detached file, as written by `sign -o`
+    #[arg(long, value_parser = verify_file, conflicts_with = "sig")]
+    pub sig_file: Option<String>,
+    // don't print the outcome, only set the exit code
     #[arg(short, long)]
-    pub sig: String,
-    #[arg(long, value_parser = parse_format, default_value = "blake3")]
-    pub format: TextSignFormat,
+    pub quiet: bool,
+    // print {"verified", "outcome", "format", "input"} as JSON, one line per input
+    #[arg(long)]
+    pub json: bool,
+    // untagged signatures are checked as this format and are malformed without it. An "ed25519:"
+    // prefix takes precedence, a blake3 or hmac-sha256 prefix must match it
+    #[arg(long, value_parser = parse_format)]
+    pub format: Option<TextSignFormat>,
+    // verify the concatenation of the files listed in this file, as signed with `sign --manifest`
+    #[arg(long, value_parser = verify_file, conflicts_with_all = ["input", "files", "passphrase"])]
+    pub manifest: Option<String>,
+    // verify each file against <file>.sig next to it
+    #[arg(value_parser = verify_file, conflicts_with_all = ["input", "sig", "sig_file"])]
+    pub files: Vec<String>,
+    // verify the "<line>\t<signature>" lines written by `sign --lines`
+    #[arg(
+        long,
+        conflicts_with_all = ["sig", "sig_file", "files", "manifest", "passphrase"]
+    )]
+    pub lines: bool,
 }
 
 #[derive(Debug, Parser)]
 pub struct TextKeyGenOpts {
     #[arg(long, value_parser = parse_format, default_value = "blake3")]
     pub format: TextSignFormat,
-    #[arg(short, long, value_parser = verify_path)]
+    // must be an existing directory unless --mkdir is given, see output_dir
+    #[arg(short, long)]
     pub output: PathBuf,
+    // create the output directory and its parents if they don't exist
+    #[arg(long)]
+    pub mkdir: bool,
+    // print a SHA-256 fingerprint of the public (or symmetric) key on stderr
+    #[arg(long)]
+    pub fingerprint: bool,
+    // overwrite existing key files
+    #[arg(long)]
+    pub force: bool,
+    // print the files that would be written and their base64 keys on stderr, write nothing
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+impl TextKeyGenOpts {
+    // the checked --output directory, created first with --mkdir unless this is a dry run
+    pub fn output_dir(&self) -> Result<PathBuf, anyhow::Error> {
+        if self.mkdir && self.dry_run && !self.output.exists() {
+            return Ok(self.output.clone());
+        }
+        verify_or_create_path(&self.output, self.mkdir)
+            .map_err(|e| anyhow::anyhow!("{}: {}", self.output.display(), e))
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct TextRecoverPubkeyOpts {
+    // the ed25519 secret key, raw or PEM; "-" reads it from stdin
+    #[arg(short, long, value_parser = verify_file)]
+    pub key: String,
+    // also write the raw public key here, like keygen's ed25519.pk
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    // overwrite an existing --output
+    #[arg(long, requires = "output")]
+    pub force: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct TextEncryptOpts {
+    // default_value_t = "-": input from stdin
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    // 32-byte key file, e.g. one generated with `text generate --format blake3`
+    #[arg(short, long, value_parser = verify_file)]
+    pub key: String,
+    #[arg(long, value_parser = parse_cipher_format, default_value = "chacha20-poly1305")]
+    pub format: TextCipherFormat,
+}
+
+#[derive(Debug, Parser)]
+pub struct TextDecryptOpts {
+    // base64 of nonce || ciphertext, as printed by `text encrypt`
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    #[arg(short, long, value_parser = verify_file)]
+    pub key: String,
+    #[arg(long, value_parser = parse_cipher_format, default_value = "chacha20-poly1305")]
+    pub format: TextCipherFormat,
 }
 
 fn parse_format(format: &str) -> Result<TextSignFormat, anyhow::Error> {
     format.parse()
 }
 
-#[derive(Debug, Clone, Copy)]
+fn parse_cipher_format(format: &str) -> Result<TextCipherFormat, anyhow::Error> {
+    format.parse()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TextSignFormat {
     Blake3,
     Ed25519,
+    HmacSha256,
 }
 
 impl FromStr for TextSignFormat {
@@ -61,6 +192,7 @@ impl FromStr for TextSignFormat {
         match s {
             "blake3" => Ok(TextSignFormat::Blake3),
             "ed25519" => Ok(TextSignFormat::Ed25519),
+            "hmac-sha256" => Ok(TextSignFormat::HmacSha256),
             v => Err(anyhow::anyhow!("Unsupported format: {}", v)),
         }
     }
@@ -71,6 +203,7 @@ impl From<TextSignFormat> for &'static str {
         match format {
             TextSignFormat::Blake3 => "blake3",
             TextSignFormat::Ed25519 => "ed25519",
+            TextSignFormat::HmacSha256 => "hmac-sha256",
         }
     }
 }
@@ -80,3 +213,36 @@ impl fmt::Display for TextSignFormat {
         write!(f, "{}", Into::<&str>::into(*self))
     }
 }
+
+#[derive(Debug, Clone, Copy)]
+pub enum TextCipherFormat {
+    Chacha20Poly1305,
+    Aes256Gcm,
+}
+
+impl FromStr for TextCipherFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "chacha20-poly1305" => Ok(TextCipherFormat::Chacha20Poly1305),
+            "aes-256-gcm" => Ok(TextCipherFormat::Aes256Gcm),
+            v => Err(anyhow::anyhow!("Unsupported format: {}", v)),
+        }
+    }
+}
+
+impl From<TextCipherFormat> for &'static str {
+    fn from(format: TextCipherFormat) -> Self {
+        match format {
+            TextCipherFormat::Chacha20Poly1305 => "chacha20-poly1305",
+            TextCipherFormat::Aes256Gcm => "aes-256-gcm",
+        }
+    }
+}
+
+impl fmt::Display for TextCipherFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
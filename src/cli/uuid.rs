@@ -0,0 +1,58 @@
+use clap::Parser;
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Parser)]
+pub struct UuidOpts {
+    // 4 is random, 7 is time-ordered and sorts by creation
+    #[arg(long, value_parser = parse_version, default_value = "4")]
+    pub version: UuidVersion,
+    // number of UUIDs to generate, one per line
+    #[arg(short, long, default_value_t = 1)]
+    pub count: usize,
+    // print A-F instead of a-f
+    #[arg(short, long)]
+    pub uppercase: bool,
+    // 8-4-4-4-12 groups, the default
+    #[arg(long)]
+    pub hyphenated: bool,
+    // 32 hex characters without hyphens
+    #[arg(long, conflicts_with = "hyphenated")]
+    pub simple: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UuidVersion {
+    V4,
+    V7,
+}
+
+fn parse_version(version: &str) -> Result<UuidVersion, anyhow::Error> {
+    version.parse()
+}
+
+impl FromStr for UuidVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "4" => Ok(UuidVersion::V4),
+            "7" => Ok(UuidVersion::V7),
+            v => Err(anyhow::anyhow!("Unsupported UUID version: {}", v)),
+        }
+    }
+}
+
+impl From<UuidVersion> for &'static str {
+    fn from(version: UuidVersion) -> Self {
+        match version {
+            UuidVersion::V4 => "4",
+            UuidVersion::V7 => "7",
+        }
+    }
+}
+
+impl fmt::Display for UuidVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
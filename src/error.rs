@@ -0,0 +1,102 @@
+use std::{io, string::FromUtf8Error};
+use thiserror::Error;
+
+// returned by the process_* functions so library users can match on the failure,
+// anything without a dedicated variant ends up in Other
+#[derive(Debug, Error)]
+pub enum RcliError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+    #[error("error at line {line}: {source}")]
+    CsvRecord { line: u64, source: csv::Error },
+    #[error("{}", base64_message(.0))]
+    Base64Decode(#[from] base64::DecodeError),
+    #[error(transparent)]
+    Utf8(#[from] FromUtf8Error),
+    #[error("invalid token signature")]
+    SignatureInvalid,
+    #[error("{algorithm} key must be at least {expected} bytes, got {found}")]
+    InvalidKeyLength {
+        algorithm: &'static str,
+        expected: usize,
+        found: usize,
+    },
+    #[error(transparent)]
+    Other(anyhow::Error),
+}
+
+// internal helpers use anyhow, recover the typed error when there is one underneath
+impl From<anyhow::Error> for RcliError {
+    fn from(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<RcliError>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+        let err = match err.downcast::<io::Error>() {
+            Ok(err) => return RcliError::Io(err),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<csv::Error>() {
+            Ok(err) => return RcliError::Csv(err),
+            Err(err) => err,
+        };
+        match err.downcast::<base64::DecodeError>() {
+            Ok(err) => RcliError::Base64Decode(err),
+            Err(err) => RcliError::Other(err),
+        }
+    }
+}
+
+// positions are 0-based offsets into the input
+pub(crate) fn base64_message(err: &base64::DecodeError) -> String {
+    use base64::DecodeError;
+
+    match *err {
+        DecodeError::InvalidByte(pos, b'=') => {
+            format!("unexpected padding '=' at position {}", pos)
+        }
+        DecodeError::InvalidByte(pos, byte) => {
+            format!("invalid character {} at position {}", show_byte(byte), pos)
+        }
+        DecodeError::InvalidLength(len) => format!(
+            "invalid length: {} base64 characters can't be decoded, the input may be truncated",
+            len
+        ),
+        DecodeError::InvalidLastSymbol(pos, byte) => format!(
+            "invalid last symbol {} at position {}, the input may be truncated or corrupted",
+            show_byte(byte),
+            pos
+        ),
+        DecodeError::InvalidPadding => {
+            "invalid padding: missing or unexpected '=' for this format".to_string()
+        }
+    }
+}
+
+fn show_byte(byte: u8) -> String {
+    if byte.is_ascii_graphic() {
+        format!("'{}'", byte as char)
+    } else {
+        format!("0x{:02x}", byte)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_anyhow_keeps_variant() {
+        let err = RcliError::from(anyhow::Error::from(io::Error::other("boom")));
+        assert!(matches!(err, RcliError::Io(_)));
+
+        let err = RcliError::from(anyhow::Error::from(RcliError::SignatureInvalid));
+        assert!(matches!(err, RcliError::SignatureInvalid));
+
+        let err = RcliError::from(anyhow::anyhow!("something else"));
+        assert!(matches!(err, RcliError::Other(_)));
+        assert_eq!(err.to_string(), "something else");
+    }
+}
@@ -1,10 +1,29 @@
 mod cli;
+mod error;
 mod process;
 mod utils;
 
-pub use cli::{Base64Format, Base64SubCommand, Opts, SubCommand, TextSignFormat, TextSubCommand};
+pub use cli::{
+    Base32SubCommand, Base64Format, Base64SubCommand, CompressAlgo, CsvOpts, Delimiter,
+    GenPassEncoding, HashAlgo, HexSubCommand, JwtAlg, JwtSubCommand, Opts, OtpAlgo, OtpSubCommand,
+    OutputFormat, SubCommand, TextCipherFormat, TextSignFormat, TextSubCommand, UuidVersion,
+};
+pub use error::RcliError;
 pub use process::{
-    process_csv, process_decode, process_encode, process_genpass, process_keygen, process_sign,
-    process_verify,
+    csv_to_json, derive_key, genpass_rng, key_fingerprint, key_paths, process_base32_decode,
+    process_base32_encode, process_completion, process_compress, process_csv, process_csv_split,
+    process_csv_stats, process_csv_validate, process_decode, process_decode_bench,
+    process_decode_literal, process_decode_stream, process_decompress, process_decrypt,
+    process_detect, process_encode, process_encode_bench, process_encode_files,
+    process_encode_literal, process_encode_stream, process_encrypt, process_genkey,
+    process_genpass, process_hash, process_hash_bench, process_hash_check, process_hex_decode,
+    process_hex_encode, process_json2csv, process_jwt_sign, process_jwt_verify, process_keygen,
+    process_keygen_to_dir, process_manpage, process_otp_generate, process_otp_verify,
+    process_passphrase, process_password_stats, process_recover_pubkey, process_sign,
+    process_sign_files, process_sign_lines, process_sign_manifest, process_sign_passphrase,
+    process_uuid, process_verify, process_verify_files, process_verify_lines,
+    process_verify_manifest, process_verify_passphrase, read_otp_secret, read_salt, read_signature,
+    signature_format, BenchReport, CsvStats, CsvSummary, DataType, DigestFormat, GenPassAlphabets,
+    GenPassConfig, JwtClaims, Totp, VerifyOutcome, VerifyReport,
 };
 pub use utils::*;
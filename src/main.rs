@@ -1,72 +1,422 @@
-use std::fs;
+use std::io::{self, Write};
 
+use base64::{engine::general_purpose::STANDARD, Engine};
 use clap::Parser;
 use rcli::{
-    process_csv, process_decode, process_encode, process_genpass, process_keygen, process_sign,
-    process_verify, Base64SubCommand, Opts, SubCommand, TextSignFormat, TextSubCommand,
+    check_overwrite, confirm_overwrite, genpass_rng, get_append_writer, get_concat_reader,
+    get_reader, get_writer, key_fingerprint, key_paths, process_base32_decode,
+    process_base32_encode, process_completion, process_compress, process_csv, process_csv_split,
+    process_csv_stats, process_csv_validate, process_decode, process_decode_bench,
+    process_decode_literal, process_decode_stream, process_decompress, process_decrypt,
+    process_detect, process_encode_bench, process_encode_files, process_encode_literal,
+    process_encode_stream, process_encrypt, process_genkey, process_genpass, process_hash,
+    process_hash_bench, process_hash_check, process_hex_decode, process_hex_encode,
+    process_json2csv, process_jwt_sign, process_jwt_verify, process_keygen_to_dir, process_manpage,
+    process_otp_generate, process_otp_verify, process_passphrase, process_password_stats,
+    process_recover_pubkey, process_sign, process_sign_files, process_sign_lines,
+    process_sign_manifest, process_sign_passphrase, process_uuid, process_verify,
+    process_verify_files, process_verify_lines, process_verify_manifest, process_verify_passphrase,
+    read_otp_secret, read_salt, read_signature, signature_format, write_output, Base32SubCommand,
+    Base64SubCommand, DigestFormat, GenPassConfig, GenPassEncoding, HexSubCommand, JwtSubCommand,
+    Opts, OtpSubCommand, SubCommand, TextSubCommand, VerifyOutcome, VerifyReport,
 };
 use zxcvbn::zxcvbn;
 
 fn main() -> anyhow::Result<()> {
     let opts = Opts::parse();
     match opts.cmd {
+        SubCommand::Csv(opts) if opts.validate => {
+            let malformed = process_csv_validate(&opts)?;
+            for row in &malformed {
+                println!("{}", row);
+            }
+            if !malformed.is_empty() {
+                anyhow::bail!("{} malformed rows", malformed.len());
+            }
+        }
+        SubCommand::Csv(opts) if opts.split => {
+            let written = process_csv_split(&opts)?;
+            if !opts.quiet {
+                eprintln!("Wrote {} row files", written.len());
+            }
+        }
+        SubCommand::Csv(opts) if opts.stats => {
+            let stats = process_csv_stats(&opts)?;
+            println!("{}", serde_json::to_string(&stats)?);
+        }
         SubCommand::Csv(opts) => {
-            let output = if let Some(output) = opts.output {
-                output
+            let output = opts.output_path();
+            process_csv(&opts, output)?;
+        }
+        SubCommand::Json2Csv(opts) => {
+            process_json2csv(&opts.input, &opts.output, opts.force)?;
+        }
+        SubCommand::GenPass(opts) => {
+            let mut rng = genpass_rng(opts.seed);
+            let output = opts.output.as_deref().unwrap_or("-");
+            let mut writer = if opts.append {
+                get_append_writer(output)?
             } else {
-                format!("output.{}", opts.format)
+                check_overwrite(output, opts.force)?;
+                get_writer(output)?
             };
-            process_csv(&opts.input, output, opts.format)?;
+            for _ in 0..opts.count {
+                if opts.encoding != GenPassEncoding::Raw {
+                    let key = process_genkey(opts.length as usize, opts.encoding, &mut rng)?;
+                    // key material has no meaningful strength estimate
+                    writeln!(writer, "{}", key)?;
+                    continue;
+                }
+                let password = if opts.passphrase {
+                    process_passphrase(opts.words, &opts.separator, &mut rng)?
+                } else {
+                    process_genpass(&GenPassConfig::from(&opts), &mut rng)?
+                };
+                writeln!(writer, "{}", password)?;
+                // output password strength in stderr
+                // 使用eprintln!只是开发时，方便查看，当>>output.passwd时不会真正输出到文件
+                if opts.stats_json {
+                    let stats = process_password_stats(&password)?;
+                    eprintln!("{}", serde_json::to_string(&stats)?);
+                } else {
+                    let estimate = zxcvbn(&password, &[])?;
+                    eprintln!("Password strength: {}", estimate.score());
+                }
+            }
+            writer.flush()?;
         }
-        SubCommand::GenPass(opts) => {
-            let password = process_genpass(
-                opts.length,
-                opts.uppercase,
-                opts.lowercase,
-                opts.number,
-                opts.symbol,
-            )?;
-            println!("{}", password);
-            // output password strength in stderr
-            let estimate = zxcvbn(&password, &[])?;
-            // 使用eprintln!只是开发时，方便查看，当>>output.passwd时不会真正输出到文件
-            eprintln!("Password strength: {}", estimate.score());
+        SubCommand::Uuid(opts) => {
+            for uuid in process_uuid(opts.version, opts.count, opts.simple, opts.uppercase)? {
+                println!("{}", uuid);
+            }
+        }
+        SubCommand::Hash(opts) => match opts.check {
+            Some(expected) => {
+                if !process_hash_check(&opts.input, opts.algo, &expected)? {
+                    anyhow::bail!("{} checksum mismatch for {}", opts.algo, opts.input);
+                }
+                println!("{}: OK", opts.input);
+            }
+            None => {
+                let format = DigestFormat::from(&opts);
+                let digest = match opts.bench {
+                    Some(runs) => {
+                        let (digest, report) =
+                            process_hash_bench(&opts.input, opts.algo, &format, runs)?;
+                        eprintln!("{}", report);
+                        digest
+                    }
+                    None => process_hash(&opts.input, opts.algo, &format)?,
+                };
+                println!("{}", digest);
+            }
+        },
+        SubCommand::Detect(opts) => {
+            println!("{}", process_detect(&opts.input)?);
+        }
+        SubCommand::Compress(opts) => {
+            let compressed = process_compress(&opts.input, opts.algo, opts.level)?;
+            write_output(opts.output, compressed, opts.force)?;
         }
+        SubCommand::Decompress(opts) => {
+            let decompressed = process_decompress(&opts.input, opts.algo)?;
+            write_output(opts.output, decompressed, opts.force)?;
+        }
+        SubCommand::Base32(subcmd) => match subcmd {
+            Base32SubCommand::Encode(opts) => {
+                println!("{}", process_base32_encode(&opts.input, !opts.no_pad)?);
+            }
+            Base32SubCommand::Decode(opts) => {
+                let decoded = process_base32_decode(&opts.input)?;
+                match opts.output {
+                    Some(output) => write_output(output, decoded, opts.force)?,
+                    None => io::stdout().write_all(&decoded)?,
+                }
+            }
+        },
         SubCommand::Base64(subcmd) => match subcmd {
+            Base64SubCommand::Encode(opts) if opts.stream => {
+                let reader = get_concat_reader(&opts.input, opts.separator.as_bytes())?;
+                let output = opts.output.as_deref().unwrap_or("-");
+                check_overwrite(output, opts.force)?;
+                let writer = get_writer(output)?;
+                process_encode_stream(reader, writer, opts.format)?;
+            }
+            Base64SubCommand::Decode(opts) if opts.stream => {
+                let reader = get_reader(&opts.input)?;
+                let output = opts.output.as_deref().unwrap_or("-");
+                check_overwrite(output, opts.force)?;
+                let writer = get_writer(output)?;
+                process_decode_stream(reader, writer, opts.format)?;
+            }
             Base64SubCommand::Encode(opts) => {
-                let encoded = process_encode(&opts.input, opts.format)?;
-                println!("{}", encoded);
+                let encoded = match (&opts.literal, opts.bench) {
+                    (Some(data), _) => process_encode_literal(data, opts.format, opts.wrap)?,
+                    (None, Some(runs)) => {
+                        let (encoded, report) = process_encode_bench(
+                            &opts.input,
+                            opts.separator.as_bytes(),
+                            opts.format,
+                            opts.wrap,
+                            runs,
+                        )?;
+                        eprintln!("{}", report);
+                        encoded
+                    }
+                    (None, None) => process_encode_files(
+                        &opts.input,
+                        opts.separator.as_bytes(),
+                        opts.format,
+                        opts.wrap,
+                    )?,
+                };
+                match opts.output {
+                    Some(output) => write_output(output, encoded, opts.force)?,
+                    None => println!("{}", encoded),
+                }
             }
             Base64SubCommand::Decode(opts) => {
-                let decoded = process_decode(&opts.input, opts.format)?;
-                let decoded = String::from_utf8(decoded)?;
-                println!("{}", decoded);
+                let decoded = match (&opts.literal, opts.bench) {
+                    (Some(data), _) => {
+                        process_decode_literal(data, opts.format, opts.ignore_garbage)?
+                    }
+                    (None, Some(runs)) => {
+                        let (decoded, report) = process_decode_bench(
+                            &opts.input,
+                            opts.format,
+                            opts.ignore_garbage,
+                            runs,
+                        )?;
+                        eprintln!("{}", report);
+                        decoded
+                    }
+                    (None, None) => process_decode(&opts.input, opts.format, opts.ignore_garbage)?,
+                };
+                match opts.output {
+                    // raw bytes go straight to the file, no utf-8 conversion
+                    Some(output) => write_output(output, decoded, opts.force)?,
+                    None if opts.text => println!("{}", String::from_utf8(decoded)?),
+                    None => io::stdout().write_all(&decoded)?,
+                }
+            }
+        },
+        SubCommand::Hex(subcmd) => match subcmd {
+            HexSubCommand::Encode(opts) => {
+                println!("{}", process_hex_encode(&opts.input, opts.upper)?);
+            }
+            HexSubCommand::Decode(opts) => {
+                let decoded = process_hex_decode(&opts.input)?;
+                match opts.output {
+                    Some(output) => write_output(output, decoded, opts.force)?,
+                    None => io::stdout().write_all(&decoded)?,
+                }
             }
         },
         SubCommand::Text(subcmd) => match subcmd {
+            TextSubCommand::Sign(opts) if !opts.files.is_empty() => {
+                let key = opts.key.unwrap_or_default();
+                for path in process_sign_files(
+                    &opts.files,
+                    &key,
+                    opts.format,
+                    !opts.untagged,
+                    opts.ttl,
+                    opts.force,
+                )? {
+                    eprintln!("Wrote {}", path.display());
+                }
+            }
+            TextSubCommand::Verify(opts) if !opts.files.is_empty() => {
+                let outcomes =
+                    process_verify_files(&opts.files, &opts.key.unwrap_or_default(), opts.format)?;
+                let mut exit_code = 0;
+                for (file, format, outcome) in outcomes {
+                    if opts.json && !opts.quiet {
+                        let report = VerifyReport::new(&file, format, outcome);
+                        println!("{}", serde_json::to_string(&report)?);
+                    } else if !opts.quiet {
+                        println!("{}: {}", file, outcome);
+                    }
+                    exit_code = exit_code.max(outcome.exit_code());
+                }
+                if exit_code != 0 {
+                    std::process::exit(exit_code);
+                }
+            }
+            TextSubCommand::Sign(opts) if opts.lines => {
+                let key = opts.key.unwrap_or_default();
+                let (format, tagged) = (opts.format, !opts.untagged);
+                let output = opts.output.unwrap_or_else(|| "-".into());
+                check_overwrite(&output, opts.force)?;
+                let writer = get_writer(&output.to_string_lossy())?;
+                process_sign_lines(&opts.input, &key, format, tagged, opts.ttl, writer)?;
+            }
+            TextSubCommand::Verify(opts) if opts.lines => {
+                let outcomes =
+                    process_verify_lines(&opts.input, &opts.key.unwrap_or_default(), opts.format)?;
+                let mut exit_code = 0;
+                for (line, format, outcome) in outcomes {
+                    if opts.json && !opts.quiet {
+                        let input = format!("{}:{}", opts.input, line);
+                        let report = VerifyReport::new(&input, format, outcome);
+                        println!("{}", serde_json::to_string(&report)?);
+                    } else if !opts.quiet {
+                        println!("line {}: {}", line, outcome);
+                    }
+                    exit_code = exit_code.max(outcome.exit_code());
+                }
+                if exit_code != 0 {
+                    std::process::exit(exit_code);
+                }
+            }
             TextSubCommand::Sign(opts) => {
-                let sig = process_sign(&opts.input, &opts.key, opts.format)?;
-                println!("{}", sig);
+                let key = opts.key.unwrap_or_default();
+                let sig = match (opts.passphrase, opts.manifest) {
+                    (Some(passphrase), _) => {
+                        let salt = read_salt(opts.salt, opts.salt_file)?;
+                        process_sign_passphrase(
+                            &opts.input,
+                            &passphrase,
+                            &salt,
+                            opts.format,
+                            !opts.untagged,
+                            opts.ttl,
+                        )?
+                    }
+                    (None, Some(manifest)) => process_sign_manifest(
+                        &manifest,
+                        &key,
+                        opts.format,
+                        !opts.untagged,
+                        opts.ttl,
+                    )?,
+                    (None, None) => {
+                        process_sign(&opts.input, &key, opts.format, !opts.untagged, opts.ttl)?
+                    }
+                };
+                match opts.output {
+                    Some(output) => write_output(output, sig, opts.force)?,
+                    None => println!("{}", sig),
+                }
             }
             TextSubCommand::Verify(opts) => {
-                let verified = process_verify(&opts.input, &opts.key, opts.sig, opts.format)?;
-                println!("{}", verified);
+                let sig = match opts.sig_file {
+                    Some(path) => read_signature(path)?,
+                    None => opts.sig.unwrap_or_default(),
+                };
+                let format = signature_format(&sig, opts.format);
+                let key = opts.key.unwrap_or_default();
+                // the manifest stands in for the input in the report
+                let input = opts.manifest.clone().unwrap_or_else(|| opts.input.clone());
+                let outcome = match (opts.passphrase, opts.manifest) {
+                    (Some(passphrase), _) => {
+                        let salt = read_salt(opts.salt, opts.salt_file)?;
+                        process_verify_passphrase(
+                            &opts.input,
+                            &passphrase,
+                            &salt,
+                            sig,
+                            opts.format,
+                        )?
+                    }
+                    (None, Some(manifest)) => {
+                        process_verify_manifest(&manifest, &key, sig, opts.format)?
+                    }
+                    (None, None) => process_verify(&opts.input, &key, sig, opts.format)?,
+                };
+                if opts.json && !opts.quiet {
+                    let report = VerifyReport::new(&input, format, outcome);
+                    println!("{}", serde_json::to_string(&report)?);
+                } else if !opts.quiet {
+                    println!("{}", outcome);
+                }
+                if outcome != VerifyOutcome::Valid {
+                    std::process::exit(outcome.exit_code());
+                }
             }
             TextSubCommand::Generate(opts) => {
-                let key = process_keygen(opts.format)?;
-                match opts.format {
-                    TextSignFormat::Blake3 => {
-                        let name = opts.output.join("blake3.key");
-                        fs::write(name, &key[0])?;
+                let output = opts.output_dir()?;
+                let mut force = opts.force;
+                if !opts.dry_run {
+                    // on a terminal each existing file is confirmed instead of failing
+                    for path in key_paths(opts.format, &output) {
+                        confirm_overwrite(path, force)?;
                     }
-                    TextSignFormat::Ed25519 => {
-                        let name = &opts.output;
-                        fs::write(name.join("ed25519.sk"), &key[0])?;
-                        fs::write(name.join("ed25519.pk"), &key[1])?;
+                    force = true;
+                }
+                let keys = process_keygen_to_dir(opts.format, &output, force, opts.dry_run)?;
+                if opts.dry_run {
+                    for (path, key) in &keys {
+                        eprintln!("Would write {}: {}", path.display(), STANDARD.encode(key));
                     }
                 }
+                if opts.fingerprint {
+                    // the last key is the public one for ed25519, the only one otherwise
+                    if let Some((_, public)) = keys.last() {
+                        eprintln!("Fingerprint: {}", key_fingerprint(public));
+                    }
+                }
+            }
+            TextSubCommand::RecoverPubkey(opts) => {
+                let pk = process_recover_pubkey(&opts.key)?;
+                if let Some(output) = opts.output {
+                    write_output(output, &pk, opts.force)?;
+                }
+                println!("{}", STANDARD.encode(&pk));
+            }
+            TextSubCommand::Encrypt(opts) => {
+                let encrypted = process_encrypt(&opts.input, &opts.key, opts.format)?;
+                println!("{}", encrypted);
+            }
+            TextSubCommand::Decrypt(opts) => {
+                let decrypted = process_decrypt(&opts.input, &opts.key, opts.format)?;
+                io::stdout().write_all(&decrypted)?;
+            }
+        },
+        SubCommand::Jwt(subcmd) => match subcmd {
+            JwtSubCommand::Sign(opts) => {
+                let token = process_jwt_sign(&opts.key, opts.alg, opts.sub, opts.aud, opts.exp)?;
+                println!("{}", token);
+            }
+            JwtSubCommand::Verify(opts) => {
+                let claims =
+                    process_jwt_verify(&opts.token, &opts.key, opts.alg, opts.aud.as_deref())?;
+                println!("{}", serde_json::to_string(&claims)?);
+            }
+        },
+        SubCommand::Otp(subcmd) => match subcmd {
+            OtpSubCommand::Generate(opts) => {
+                let o = opts.secret;
+                let secret = match o.secret_file {
+                    Some(path) => read_otp_secret(&path)?,
+                    None => o.secret.unwrap_or_default(),
+                };
+                println!(
+                    "{}",
+                    process_otp_generate(&secret, o.digits, o.period, o.algo)?
+                );
+            }
+            OtpSubCommand::Verify(opts) => {
+                let o = opts.secret;
+                let secret = match o.secret_file {
+                    Some(path) => read_otp_secret(&path)?,
+                    None => o.secret.unwrap_or_default(),
+                };
+                let valid = process_otp_verify(&secret, &opts.code, o.digits, o.period, o.algo)?;
+                println!("{}", if valid { "valid" } else { "invalid" });
+                if !valid {
+                    std::process::exit(1);
+                }
             }
         },
+        SubCommand::Completion(opts) => {
+            process_completion(opts.shell, io::stdout())?;
+        }
+        SubCommand::Manpage(opts) => {
+            for path in process_manpage(&opts.out_dir)? {
+                eprintln!("Wrote {}", path.display());
+            }
+        }
     }
     Ok(())
 }
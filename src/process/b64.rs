@@ -1,43 +1,501 @@
-use crate::{get_buf, Base64Format};
-use anyhow::Result;
-use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
-use base64::Engine;
+use super::bench::{bench, BenchReport};
+use crate::{error::base64_message, get_buf_bytes, get_concat_reader, Base64Format, RcliError};
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::{
+    GeneralPurpose, GeneralPurposeConfig, STANDARD, URL_SAFE, URL_SAFE_NO_PAD,
+};
+use base64::engine::DecodePaddingMode;
+use base64::read::DecoderReader;
+use base64::write::EncoderWriter;
+use base64::{alphabet, DecodeError, Engine};
+use std::io::{self, BufRead, BufReader, Read, Write};
 
-pub fn process_encode(input: &str, format: Base64Format) -> Result<String> {
-    let buf = get_buf(input)?;
+// auto-detection accepts both padded and unpadded input
+const AUTO_CONFIG: GeneralPurposeConfig =
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+const AUTO_STANDARD: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, AUTO_CONFIG);
+const AUTO_URL_SAFE: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, AUTO_CONFIG);
+
+pub fn process_encode(input: &str, format: Base64Format, wrap: usize) -> Result<String, RcliError> {
+    let buf = get_buf_bytes(input)?;
+    Ok(encode_bytes(&buf, format, wrap)?)
+}
+
+// `data` is the payload itself, not a path
+pub fn process_encode_literal(
+    data: &str,
+    format: Base64Format,
+    wrap: usize,
+) -> Result<String, RcliError> {
+    Ok(encode_bytes(data.as_bytes(), format, wrap)?)
+}
+
+// the inputs are concatenated in order, with `separator` between each pair
+pub fn process_encode_files(
+    inputs: &[String],
+    separator: &[u8],
+    format: Base64Format,
+    wrap: usize,
+) -> Result<String, RcliError> {
+    let mut buf = Vec::new();
+    get_concat_reader(inputs, separator)?.read_to_end(&mut buf)?;
+    Ok(encode_bytes(&buf, format, wrap)?)
+}
+
+// encodes the loaded inputs `runs` times, the output is the same as process_encode_files
+pub fn process_encode_bench(
+    inputs: &[String],
+    separator: &[u8],
+    format: Base64Format,
+    wrap: usize,
+    runs: u32,
+) -> Result<(String, BenchReport), RcliError> {
+    let mut buf = Vec::new();
+    get_concat_reader(inputs, separator)?.read_to_end(&mut buf)?;
+    Ok(bench(runs, buf.len(), || encode_bytes(&buf, format, wrap))?)
+}
+
+fn encode_bytes(buf: &[u8], format: Base64Format, wrap: usize) -> Result<String> {
     let encoded = match format {
         Base64Format::Standard => STANDARD.encode(buf),
         Base64Format::UrlSafe => URL_SAFE_NO_PAD.encode(buf),
+        Base64Format::UrlSafePad => URL_SAFE.encode(buf),
+        Base64Format::Auto => return Err(anyhow!("auto format is only supported for decoding")),
     };
 
-    Ok(encoded)
+    Ok(wrap_lines(&encoded, wrap))
+}
+
+// with `ignore_garbage` every character outside the format's alphabet is dropped first
+pub fn process_decode(
+    input: &str,
+    format: Base64Format,
+    ignore_garbage: bool,
+) -> Result<Vec<u8>, RcliError> {
+    Ok(decode_bytes(
+        &get_buf_bytes(input)?,
+        format,
+        ignore_garbage,
+    )?)
+}
+
+// decodes the loaded input `runs` times, the output is the same as process_decode
+pub fn process_decode_bench(
+    input: &str,
+    format: Base64Format,
+    ignore_garbage: bool,
+    runs: u32,
+) -> Result<(Vec<u8>, BenchReport), RcliError> {
+    let buf = get_buf_bytes(input)?;
+    Ok(bench(runs, buf.len(), || {
+        decode_bytes(&buf, format, ignore_garbage)
+    })?)
+}
+
+// `data` is the encoded string itself, not a path
+pub fn process_decode_literal(
+    data: &str,
+    format: Base64Format,
+    ignore_garbage: bool,
+) -> Result<Vec<u8>, RcliError> {
+    Ok(decode_bytes(data.as_bytes(), format, ignore_garbage)?)
 }
 
-pub fn process_decode(input: &str, format: Base64Format) -> Result<Vec<u8>> {
-    let buf = get_buf(input)?;
+fn decode_bytes(input: &[u8], format: Base64Format, ignore_garbage: bool) -> Result<Vec<u8>> {
+    // like GNU base64 -i, error positions then refer to the cleaned input
+    let cleaned;
+    let input = if ignore_garbage {
+        cleaned = strip_garbage(input, format);
+        cleaned.as_slice()
+    } else {
+        input
+    };
+    // wrapped (MIME/PEM style) input contains newlines
+    let buf: Vec<u8> = input
+        .iter()
+        .copied()
+        .filter(|c| !c.is_ascii_whitespace())
+        .collect();
     let decoded = match format {
-        Base64Format::Standard => STANDARD.decode(buf)?,
-        Base64Format::UrlSafe => URL_SAFE_NO_PAD.decode(buf)?,
+        Base64Format::Standard => STANDARD.decode(&buf),
+        Base64Format::UrlSafe => URL_SAFE_NO_PAD.decode(&buf),
+        Base64Format::UrlSafePad => URL_SAFE.decode(&buf),
+        Base64Format::Auto => return decode_auto(&buf),
+    };
+
+    decoded.map_err(|e| input_position(e, input).into())
+}
+
+// keeps the alphabet of `format` and '=', auto keeps both alphabets
+fn strip_garbage(input: &[u8], format: Base64Format) -> Vec<u8> {
+    let extra: &[u8] = match format {
+        Base64Format::Standard => b"+/=",
+        Base64Format::UrlSafe | Base64Format::UrlSafePad => b"-_=",
+        Base64Format::Auto => b"+/-_=",
     };
+    input
+        .iter()
+        .copied()
+        .filter(|c| c.is_ascii_alphanumeric() || extra.contains(c))
+        .collect()
+}
 
-    Ok(decoded)
+// offsets from the decoder skip the stripped whitespace, point them back into the input
+fn input_position(err: DecodeError, input: &[u8]) -> DecodeError {
+    let position = |offset: usize| {
+        input
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| !c.is_ascii_whitespace())
+            .nth(offset)
+            .map_or(offset, |(pos, _)| pos)
+    };
+    match err {
+        DecodeError::InvalidByte(offset, byte) => DecodeError::InvalidByte(position(offset), byte),
+        DecodeError::InvalidLastSymbol(offset, byte) => {
+            DecodeError::InvalidLastSymbol(position(offset), byte)
+        }
+        err => err,
+    }
+}
+
+pub fn process_encode_stream(
+    reader: impl Read,
+    writer: impl Write,
+    format: Base64Format,
+) -> Result<(), RcliError> {
+    let engine = match format {
+        Base64Format::Standard => &STANDARD,
+        Base64Format::UrlSafe => &URL_SAFE_NO_PAD,
+        Base64Format::UrlSafePad => &URL_SAFE,
+        Base64Format::Auto => {
+            return Err(anyhow!("auto format is only supported for decoding").into())
+        }
+    };
+    let mut encoder = EncoderWriter::new(writer, engine);
+    io::copy(&mut BufReader::new(reader), &mut encoder)?;
+    encoder.finish()?.flush()?;
+
+    Ok(())
+}
+
+pub fn process_decode_stream(
+    reader: impl Read,
+    mut writer: impl Write,
+    format: Base64Format,
+) -> Result<(), RcliError> {
+    let mut reader = BufReader::new(SkipWhitespace { inner: reader });
+    let engine = match format {
+        Base64Format::Standard => &STANDARD,
+        Base64Format::UrlSafe => &URL_SAFE_NO_PAD,
+        Base64Format::UrlSafePad => &URL_SAFE,
+        // there's no going back in a stream, so pick the alphabet from the first chunk
+        Base64Format::Auto if reader.fill_buf()?.iter().any(|c| b"-_".contains(c)) => {
+            &AUTO_URL_SAFE
+        }
+        Base64Format::Auto => &AUTO_STANDARD,
+    };
+    let mut decoder = DecoderReader::new(reader, engine);
+    io::copy(&mut decoder, &mut writer)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+// drops newlines and other whitespace so that wrapped input can be streamed
+struct SkipWhitespace<R> {
+    inner: R,
+}
+
+impl<R: Read> Read for SkipWhitespace<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = self.inner.read(buf)?;
+            if n == 0 {
+                return Ok(0);
+            }
+            let mut len = 0;
+            for i in 0..n {
+                if !buf[i].is_ascii_whitespace() {
+                    buf[len] = buf[i];
+                    len += 1;
+                }
+            }
+            if len > 0 {
+                return Ok(len);
+            }
+        }
+    }
+}
+
+fn wrap_lines(encoded: &str, width: usize) -> String {
+    if width == 0 {
+        return encoded.to_string();
+    }
+    // base64 output is ASCII so byte chunks are char boundaries
+    encoded
+        .as_bytes()
+        .chunks(width)
+        .map(|line| std::str::from_utf8(line).expect("base64 is ascii"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub(super) fn decode_auto(buf: &[u8]) -> Result<Vec<u8>> {
+    AUTO_STANDARD.decode(buf).or_else(|standard_err| {
+        AUTO_URL_SAFE.decode(buf).map_err(|urlsafe_err| {
+            anyhow!(
+                "not valid base64, tried standard ({}) and urlsafe ({})",
+                base64_message(&standard_err),
+                base64_message(&urlsafe_err)
+            )
+        })
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
 
     #[test]
     fn test_process_encode() {
         let input = "Cargo.toml";
         let format = Base64Format::Standard;
-        assert!(process_encode(input, format).is_ok())
+        assert!(process_encode(input, format, 0).is_ok())
     }
 
     #[test]
     fn test_process_decode() {
         let input = "fixtures/b64.txt";
         let format = Base64Format::Standard;
-        assert!(process_decode(input, format).is_ok())
+        assert!(process_decode(input, format, false).is_ok())
+    }
+
+    #[test]
+    fn test_process_literal() -> Result<()> {
+        let encoded = process_encode_literal("Hello", Base64Format::Standard, 0)?;
+        assert_eq!(encoded, "SGVsbG8=");
+        let decoded = process_decode_literal("SGVsbG8", Base64Format::Auto, false)?;
+        assert_eq!(decoded, b"Hello");
+        // a literal that happens to name a file is still treated as data
+        let encoded = process_encode_literal("Cargo.toml", Base64Format::Standard, 0)?;
+        assert_eq!(encoded, "Q2FyZ28udG9tbA==");
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_encode_decode_round_trip() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let encoded_path = dir.path().join("encoded.txt");
+        let encoded_path = encoded_path.to_str().expect("temp path should be utf-8");
+        let original = fs::read_to_string("Cargo.toml")?;
+
+        for format in [
+            Base64Format::Standard,
+            Base64Format::UrlSafe,
+            Base64Format::UrlSafePad,
+        ] {
+            let encoded = process_encode("Cargo.toml", format, 0)?;
+            fs::write(encoded_path, &encoded)?;
+            let decoded = process_decode(encoded_path, format, false)?;
+            assert_eq!(String::from_utf8(decoded)?, original);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_encode_urlsafe_pad() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let input = dir.path().join("input.txt");
+        fs::write(&input, ">>>?")?;
+        let input = input.to_str().expect("temp path should be utf-8");
+
+        assert_eq!(
+            process_encode(input, Base64Format::Standard, 0)?,
+            "Pj4+Pw=="
+        );
+        assert_eq!(process_encode(input, Base64Format::UrlSafe, 0)?, "Pj4-Pw");
+        assert_eq!(
+            process_encode(input, Base64Format::UrlSafePad, 0)?,
+            "Pj4-Pw=="
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_encode_files() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let joined = dir.path().join("joined.txt");
+        let mut buf = fs::read("fixtures/b64.txt")?;
+        buf.extend(b"\n");
+        buf.extend(fs::read("Cargo.toml")?);
+        fs::write(&joined, buf)?;
+
+        let inputs = ["fixtures/b64.txt".to_string(), "Cargo.toml".to_string()];
+        assert_eq!(
+            process_encode_files(&inputs, b"\n", Base64Format::Standard, 0)?,
+            process_encode(joined.to_str().unwrap(), Base64Format::Standard, 0)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_auto() -> Result<()> {
+        // "+" and "/" only exist in the standard alphabet
+        assert_eq!(decode_auto(b"Pj4+Pw==")?, b">>>?");
+        assert_eq!(decode_auto(b"Pz4/")?, b"?>?");
+        // "-" and "_" only exist in the url-safe alphabet, with or without padding
+        assert_eq!(decode_auto(b"Pj4-Pw")?, b">>>?");
+        assert_eq!(decode_auto(b"Pj4-Pw==")?, b">>>?");
+        assert_eq!(decode_auto(b"Pz4_")?, b"?>?");
+
+        let err = decode_auto(b"Pj4+Pw_").unwrap_err();
+        assert!(err.to_string().contains("tried standard"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_encode_auto() {
+        assert!(process_encode("Cargo.toml", Base64Format::Auto, 0).is_err());
+    }
+
+    #[test]
+    fn test_process_encode_wrap() -> Result<()> {
+        let encoded = process_encode("Cargo.toml", Base64Format::Standard, 64)?;
+        let lines: Vec<_> = encoded.lines().collect();
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|l| l.len() <= 64));
+        assert!(lines[..lines.len() - 1].iter().all(|l| l.len() == 64));
+        assert_eq!(
+            encoded.replace('\n', ""),
+            process_encode("Cargo.toml", Base64Format::Standard, 0)?
+        );
+
+        let dir = tempfile::tempdir()?;
+        let encoded_path = dir.path().join("wrapped.txt");
+        fs::write(&encoded_path, &encoded)?;
+        let decoded = process_decode(
+            encoded_path.to_str().expect("temp path should be utf-8"),
+            Base64Format::Standard,
+            false,
+        )?;
+        assert_eq!(
+            String::from_utf8(decoded)?,
+            fs::read_to_string("Cargo.toml")?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_stream_round_trip() -> Result<()> {
+        let data: Vec<u8> = (0..8 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+
+        for format in [
+            Base64Format::Standard,
+            Base64Format::UrlSafe,
+            Base64Format::UrlSafePad,
+        ] {
+            let mut encoded = Vec::new();
+            process_encode_stream(data.as_slice(), &mut encoded, format)?;
+            let expected = match format {
+                Base64Format::Standard => STANDARD.encode(&data),
+                Base64Format::UrlSafe => URL_SAFE_NO_PAD.encode(&data),
+                _ => URL_SAFE.encode(&data),
+            };
+            assert_eq!(encoded, expected.as_bytes());
+
+            let mut decoded = Vec::new();
+            process_decode_stream(encoded.as_slice(), &mut decoded, format)?;
+            assert_eq!(decoded, data);
+
+            let mut decoded = Vec::new();
+            process_decode_stream(encoded.as_slice(), &mut decoded, Base64Format::Auto)?;
+            assert_eq!(decoded, data);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_decode_stream_wrapped() -> Result<()> {
+        let encoded = process_encode("Cargo.toml", Base64Format::Standard, 64)? + "\n";
+        let mut decoded = Vec::new();
+        process_decode_stream(encoded.as_bytes(), &mut decoded, Base64Format::Standard)?;
+        assert_eq!(
+            String::from_utf8(decoded)?,
+            fs::read_to_string("Cargo.toml")?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_encode_binary() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let input = dir.path().join("binary.bin");
+        let data = [0x00, 0x9f, 0x92, 0x96, 0xff, 0xfe, 0x01, 0x80, 0x7f];
+        fs::write(&input, data)?;
+        let input = input.to_str().expect("temp path should be utf-8");
+
+        let encoded = process_encode(input, Base64Format::Standard, 0)?;
+        assert_eq!(encoded, "AJ+Slv/+AYB/");
+        assert_eq!(
+            process_decode("fixtures/binary.b64", Base64Format::Standard, false)?,
+            data
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_encode_keeps_whitespace() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let input = dir.path().join("input.txt");
+        fs::write(&input, "hello \n")?;
+        let input = input.to_str().expect("temp path should be utf-8");
+
+        let encoded = process_encode(input, Base64Format::Standard, 0)?;
+        assert_eq!(STANDARD.decode(encoded)?, b"hello \n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_decode_error_variant() {
+        let err = process_decode_literal("not base64!", Base64Format::Standard, false).unwrap_err();
+        assert!(matches!(err, RcliError::Base64Decode(_)));
+    }
+
+    #[test]
+    fn test_process_decode_error_messages() {
+        let decode = |data| {
+            process_decode_literal(data, Base64Format::Standard, false)
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(decode("aGVs!G8="), "invalid character '!' at position 4");
+        // the position counts the line breaks of wrapped input
+        assert_eq!(
+            decode("aGVs\nbG8g\nd2\u{7f}9y"),
+            "invalid character 0x7f at position 12"
+        );
+        assert_eq!(decode("aG=sbG8="), "unexpected padding '=' at position 2");
+        assert!(decode("aGVsb").starts_with("invalid length"));
+        assert!(decode("aGVsbG9=").starts_with("invalid last symbol '9' at position 6"));
+        assert!(decode("aGVsbG8").starts_with("invalid padding"));
+    }
+
+    #[test]
+    fn test_process_decode_ignore_garbage() -> Result<()> {
+        let data = "SG Vs\tbG*8g d2 9y#bGQ=";
+        assert!(process_decode_literal(data, Base64Format::Standard, false).is_err());
+        assert_eq!(
+            process_decode_literal(data, Base64Format::Standard, true)?,
+            b"Hello world"
+        );
+        // '-' and '_' are garbage for standard but part of the url-safe alphabet
+        assert_eq!(
+            process_decode_literal("-_-_", Base64Format::UrlSafe, true)?,
+            URL_SAFE_NO_PAD.decode("-_-_")?
+        );
+        assert!(process_decode_literal("-_-_", Base64Format::Standard, true)?.is_empty());
+        Ok(())
     }
 }
@@ -0,0 +1,79 @@
+use crate::{get_buf_bytes, RcliError};
+use anyhow::Result;
+use data_encoding::{BASE32, BASE32_NOPAD};
+
+pub fn process_base32_encode(input: &str, pad: bool) -> Result<String, RcliError> {
+    let buf = get_buf_bytes(input)?;
+    Ok(base32_encode(&buf, pad))
+}
+
+pub fn process_base32_decode(input: &str) -> Result<Vec<u8>, RcliError> {
+    let buf = get_buf_bytes(input)?;
+    Ok(base32_decode(&String::from_utf8(buf)?)?)
+}
+
+fn base32_encode(buf: &[u8], pad: bool) -> String {
+    if pad {
+        BASE32.encode(buf)
+    } else {
+        BASE32_NOPAD.encode(buf)
+    }
+}
+
+// "mzxw 6ytb oi======" and "MZXW6YTBOI" decode to the same bytes
+pub(super) fn base32_decode(input: &str) -> Result<Vec<u8>> {
+    let normalized: String = input
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '=')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    Ok(BASE32_NOPAD.decode(normalized.as_bytes())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_base32_rfc4648_vectors() -> Result<()> {
+        // RFC 4648 section 10
+        for (plain, encoded) in [
+            ("", ""),
+            ("f", "MY======"),
+            ("fo", "MZXQ===="),
+            ("foo", "MZXW6==="),
+            ("foob", "MZXW6YQ="),
+            ("fooba", "MZXW6YTB"),
+            ("foobar", "MZXW6YTBOI======"),
+        ] {
+            assert_eq!(base32_encode(plain.as_bytes(), true), encoded);
+            assert_eq!(
+                base32_encode(plain.as_bytes(), false),
+                encoded.trim_end_matches('=')
+            );
+            assert_eq!(base32_decode(encoded)?, plain.as_bytes());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_base32_decode_case_insensitive() -> Result<()> {
+        assert_eq!(base32_decode("mzxw6ytboi")?, b"foobar");
+        assert_eq!(base32_decode("MzXw 6yTb\noI======\n")?, b"foobar");
+        assert!(base32_decode("MZXW1").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_base32_round_trip() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let original = fs::read("fixtures/ed25519.sk")?;
+        for pad in [false, true] {
+            let path = dir.path().join("key.b32");
+            fs::write(&path, process_base32_encode("fixtures/ed25519.sk", pad)?)?;
+            assert_eq!(process_base32_decode(path.to_str().unwrap())?, original);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use anyhow::Result;
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+// timing of `runs` repetitions of an operation over a `bytes` long buffer
+#[derive(Debug, Clone, Copy)]
+pub struct BenchReport {
+    pub runs: u32,
+    pub bytes: usize,
+    pub elapsed: Duration,
+}
+
+impl BenchReport {
+    pub fn ops_per_sec(&self) -> f64 {
+        self.runs as f64 / self.secs()
+    }
+
+    // decimal megabytes, like most throughput figures
+    pub fn mb_per_sec(&self) -> f64 {
+        self.bytes as f64 * self.runs as f64 / 1e6 / self.secs()
+    }
+
+    // very fast runs can round down to zero
+    fn secs(&self) -> f64 {
+        self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} runs over {} bytes in {:.3?}: {:.1} ops/sec, {:.2} MB/s",
+            self.runs,
+            self.bytes,
+            self.elapsed,
+            self.ops_per_sec(),
+            self.mb_per_sec()
+        )
+    }
+}
+
+// runs `op` `runs` times (at least once) and returns the last result with the timing
+pub(super) fn bench<T>(
+    runs: u32,
+    bytes: usize,
+    mut op: impl FnMut() -> Result<T>,
+) -> Result<(T, BenchReport)> {
+    let runs = runs.max(1);
+    let start = Instant::now();
+    let mut output = op()?;
+    for _ in 1..runs {
+        output = op()?;
+    }
+    let report = BenchReport {
+        runs,
+        bytes,
+        elapsed: start.elapsed(),
+    };
+    Ok((output, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bench_runs_op() -> Result<()> {
+        let mut calls = 0;
+        let (output, report) = bench(3, 10, || {
+            calls += 1;
+            Ok(calls)
+        })?;
+        assert_eq!(output, 3);
+        assert_eq!(report.runs, 3);
+
+        let report = BenchReport {
+            runs: 4,
+            bytes: 500_000,
+            elapsed: Duration::from_secs(2),
+        };
+        assert_eq!(report.ops_per_sec(), 2.0);
+        assert_eq!(report.mb_per_sec(), 1.0);
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+use crate::{Opts, RcliError};
+use anyhow::Result;
+use clap::CommandFactory;
+use clap_complete::{generate, Shell};
+use std::io::Write;
+
+pub fn process_completion(shell: Shell, mut writer: impl Write) -> Result<(), RcliError> {
+    let mut cmd = Opts::command();
+    let name = cmd.get_name().to_string();
+    generate(shell, &mut cmd, name, &mut writer);
+    Ok(writer.flush()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_completion() -> Result<()> {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let mut buf = Vec::new();
+            process_completion(shell, &mut buf)?;
+            let script = String::from_utf8(buf)?;
+            assert!(!script.is_empty());
+            assert!(script.contains("rcli"));
+        }
+        Ok(())
+    }
+}
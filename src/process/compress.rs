@@ -0,0 +1,91 @@
+use crate::{get_buf_bytes, CompressAlgo, RcliError};
+use anyhow::{anyhow, Result};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use std::io::{Read, Write};
+
+pub fn process_compress(
+    input: &str,
+    algo: CompressAlgo,
+    level: Option<i32>,
+) -> Result<Vec<u8>, RcliError> {
+    let buf = get_buf_bytes(input)?;
+    Ok(compress_bytes(&buf, algo, level)?)
+}
+
+pub fn process_decompress(input: &str, algo: CompressAlgo) -> Result<Vec<u8>, RcliError> {
+    let buf = get_buf_bytes(input)?;
+    Ok(decompress_bytes(&buf, algo)?)
+}
+
+fn compress_bytes(data: &[u8], algo: CompressAlgo, level: Option<i32>) -> Result<Vec<u8>> {
+    match algo {
+        CompressAlgo::Zstd => {
+            // zstd also takes 0 (its default) and negative fast levels, only 1-22 are offered
+            let level = level.unwrap_or(zstd::DEFAULT_COMPRESSION_LEVEL);
+            if !(1..=22).contains(&level) {
+                return Err(anyhow!("zstd level must be between 1 and 22"));
+            }
+            Ok(zstd::encode_all(data, level)?)
+        }
+        CompressAlgo::Gzip => {
+            let level = match level {
+                Some(level @ 0..=9) => Compression::new(level as u32),
+                Some(_) => return Err(anyhow!("gzip level must be between 0 and 9")),
+                None => Compression::default(),
+            };
+            let mut encoder = GzEncoder::new(Vec::new(), level);
+            encoder.write_all(data)?;
+            Ok(encoder.finish()?)
+        }
+    }
+}
+
+fn decompress_bytes(data: &[u8], algo: CompressAlgo) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    match algo {
+        CompressAlgo::Zstd => buf = zstd::decode_all(data)?,
+        CompressAlgo::Gzip => {
+            GzDecoder::new(data).read_to_end(&mut buf)?;
+        }
+    }
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let original = fs::read("fixtures/binary.bin")?;
+        for algo in [CompressAlgo::Zstd, CompressAlgo::Gzip] {
+            let compressed = process_compress("fixtures/binary.bin", algo, None)?;
+            assert!(compressed.len() < original.len());
+            assert_eq!(decompress_bytes(&compressed, algo)?, original);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_compress_level() -> Result<()> {
+        let original = fs::read("fixtures/binary.bin")?;
+        for level in [1, 19] {
+            let compressed = compress_bytes(&original, CompressAlgo::Zstd, Some(level))?;
+            assert_eq!(decompress_bytes(&compressed, CompressAlgo::Zstd)?, original);
+        }
+        for level in [-1, 0, 23] {
+            let err = compress_bytes(&original, CompressAlgo::Zstd, Some(level)).unwrap_err();
+            assert_eq!(err.to_string(), "zstd level must be between 1 and 22");
+        }
+        assert!(compress_bytes(&original, CompressAlgo::Gzip, Some(10)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decompress_wrong_algo() -> Result<()> {
+        let compressed = process_compress("fixtures/binary.bin", CompressAlgo::Gzip, None)?;
+        assert!(decompress_bytes(&compressed, CompressAlgo::Zstd).is_err());
+        Ok(())
+    }
+}
@@ -1,37 +1,1456 @@
-use crate::cli::OutputFormat;
-use anyhow::Result;
-use csv::Reader;
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
-use std::fs;
-
-#[derive(Debug, Deserialize, Serialize)]
-#[serde(rename_all = "PascalCase")]
-struct Player {
-    name: String,
-    position: String,
-    #[serde(rename = "DOB")]
-    dob: String,
-    nationality: String,
-    #[serde(rename = "Kit Number")]
-    kit: u8,
-}
-
-pub fn process_csv(input: &str, output: String, format: OutputFormat) -> Result<()> {
-    let mut reader = Reader::from_path(input)?;
-    let mut ret = Vec::with_capacity(128);
-    let headers = reader.headers()?.clone();
-    for result in reader.records() {
-        let record = result?;
-        let json_value = headers.iter().zip(record.iter()).collect::<Value>();
-        ret.push(json_value);
-    }
-
-    let content = match format {
-        OutputFormat::Json => serde_json::to_string_pretty(&ret)?,
-        OutputFormat::Yaml => serde_yaml::to_string(&ret)?,
-    };
-    fs::write(output, content)?;
+use crate::{
+    check_overwrite, get_buf, get_reader, get_writer, is_url, process_encode_literal, Base64Format,
+    CsvOpts, Delimiter, OutputFormat, RcliError,
+};
+use anyhow::{anyhow, Result};
+use csv::{Reader, ReaderBuilder, StringRecord, Trim};
+use flate2::read::MultiGzDecoder;
+use indicatif::{ProgressBar, ProgressFinish, ProgressStyle};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::{
+    collections::HashSet,
+    fmt, fs,
+    io::{BufRead, BufReader, Read, Write},
+    path::PathBuf,
+};
 
+// writes records one at a time so that memory stays bounded for large inputs
+struct RecordWriter<W: Write> {
+    writer: W,
+    format: OutputFormat,
+    pretty: bool,
+    count: usize,
+    // --raw-headers: rows are arrays and the header row is written once up front
+    raw_headers: Option<Vec<String>>,
+}
+
+impl<W: Write> RecordWriter<W> {
+    fn new(writer: W, format: OutputFormat, pretty: bool) -> Self {
+        RecordWriter {
+            writer,
+            format,
+            pretty,
+            count: 0,
+            raw_headers: None,
+        }
+    }
+
+    fn with_raw_headers(mut self, headers: Vec<String>) -> Result<Self> {
+        if !matches!(self.format, OutputFormat::Json | OutputFormat::Ndjson) {
+            return Err(anyhow!(
+                "--raw-headers only supports json and ndjson output, not {}",
+                self.format
+            ));
+        }
+        self.raw_headers = Some(headers);
+        Ok(self)
+    }
+
+    // json: {"headers": [...], "rows": [...]}, ndjson: the headers array is the first line
+    fn write_raw_headers(&mut self) -> Result<()> {
+        let Some(headers) = &self.raw_headers else {
+            return Ok(());
+        };
+        match self.format {
+            OutputFormat::Json => {
+                self.writer.write_all(b"{\"headers\":")?;
+                serde_json::to_writer(&mut self.writer, headers)?;
+                self.writer.write_all(b",\"rows\":")?;
+            }
+            _ => {
+                serde_json::to_writer(&mut self.writer, headers)?;
+                self.writer.write_all(b"\n")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, record: &Value) -> Result<()> {
+        if self.count == 0 {
+            self.write_raw_headers()?;
+        }
+        match self.format {
+            OutputFormat::Json => {
+                let sep: &[u8] = if self.count == 0 { b"[\n" } else { b",\n" };
+                self.writer.write_all(sep)?;
+                if self.pretty {
+                    // indent one level so the array reads like to_string_pretty of all rows
+                    let pretty = serde_json::to_string_pretty(record)?;
+                    let indented = pretty.lines().collect::<Vec<_>>().join("\n  ");
+                    write!(self.writer, "  {}", indented)?;
+                } else {
+                    serde_json::to_writer(&mut self.writer, record)?;
+                }
+            }
+            OutputFormat::Yaml => serde_yaml::to_writer(&mut self.writer, &[record])?,
+            OutputFormat::Ndjson => {
+                serde_json::to_writer(&mut self.writer, record)?;
+                self.writer.write_all(b"\n")?;
+            }
+            OutputFormat::Xml => {
+                if self.count == 0 {
+                    self.writer.write_all(b"<records>\n")?;
+                }
+                self.writer.write_all(b"  <record>")?;
+                for (name, value) in record.as_object().into_iter().flatten() {
+                    let value = value.as_str().unwrap_or_default();
+                    write!(
+                        self.writer,
+                        "<field name=\"{}\">{}</field>",
+                        escape_xml(name),
+                        escape_xml(value)
+                    )?;
+                }
+                self.writer.write_all(b"</record>\n")?;
+            }
+            OutputFormat::Toml => {
+                // each row becomes a [[records]] table, all cells stay strings
+                if self.count > 0 {
+                    self.writer.write_all(b"\n")?;
+                }
+                self.writer.write_all(b"[[records]]\n")?;
+                // toml has no null, --null-value cells are left out of the table
+                let table: Map<String, Value> = record
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .filter(|(_, value)| !value.is_null())
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect();
+                self.writer.write_all(toml::to_string(&table)?.as_bytes())?;
+            }
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<usize> {
+        if self.count == 0 {
+            self.write_raw_headers()?;
+        }
+        let raw = self.raw_headers.is_some();
+        match (self.format, self.count) {
+            (OutputFormat::Json, 0) if raw => self.writer.write_all(b"[]}\n")?,
+            (OutputFormat::Json, _) if raw => self.writer.write_all(b"\n]}\n")?,
+            (OutputFormat::Json, 0) => self.writer.write_all(b"[]\n")?,
+            (OutputFormat::Json, _) => self.writer.write_all(b"\n]\n")?,
+            (OutputFormat::Yaml, 0) => self.writer.write_all(b"[]\n")?,
+            (OutputFormat::Toml, 0) => self.writer.write_all(b"records = []\n")?,
+            (OutputFormat::Xml, 0) => self.writer.write_all(b"<records></records>\n")?,
+            (OutputFormat::Xml, _) => self.writer.write_all(b"</records>\n")?,
+            _ => {}
+        }
+        self.writer.flush()?;
+        Ok(self.count)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CsvSummary {
+    pub rows: usize,
+    pub columns: usize,
+}
+
+impl fmt::Display for CsvSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Converted {} rows, {} columns", self.rows, self.columns)
+    }
+}
+
+// output "-" writes to stdout
+pub fn process_csv(opts: &CsvOpts, output: String) -> Result<(), RcliError> {
+    // an unknown -o extension fails before the file is created
+    opts.output_format()?;
+    check_overwrite(&output, opts.force)?;
+    let writer = get_writer(&output)?;
+    let summary = convert_csv(opts, writer)?;
+    // summary goes to stderr so it never mixes with the converted output
+    if !opts.quiet {
+        eprintln!("{}", summary);
+    }
     Ok(())
 }
+
+fn convert_csv(opts: &CsvOpts, mut writer: impl Write) -> Result<CsvSummary> {
+    let input = open_input(&opts.input, opts.progress, opts.timeout)?;
+    if !opts.base64 {
+        return Ok(csv_to_json(input, writer, opts)?);
+    }
+    // the output has to be complete before it can be encoded
+    let mut buf = Vec::new();
+    let summary = csv_to_json(input, &mut buf, opts)?;
+    let encoded = process_encode_literal(&String::from_utf8(buf)?, Base64Format::Standard, 0)?;
+    writeln!(writer, "{}", encoded)?;
+    Ok(summary)
+}
+
+// in-memory conversion for library use. The parsing options (delimiter, quote, escape,
+// no_quoting, no_header, trim), the row selection (columns, columns_file, filter, skip, limit,
+// null_value) and the output shape (format, or the output extension it is inferred from, pretty,
+// compact, raw_headers) apply, and validate lets ragged rows through instead of failing.
+// input, output_dir, force, quiet, progress, timeout, base64, split and stats are ignored.
+pub fn csv_to_json(
+    reader: impl Read,
+    writer: impl Write,
+    opts: &CsvOpts,
+) -> Result<CsvSummary, RcliError> {
+    let format = opts.output_format()?;
+    if opts.pretty && matches!(format, OutputFormat::Ndjson) {
+        return Err(anyhow!("--pretty cannot be used with ndjson output").into());
+    }
+
+    let mut rows = Rows::open(reader, opts)?;
+    let mut writer = RecordWriter::new(writer, format, !opts.compact);
+    if opts.raw_headers {
+        writer = writer.with_raw_headers(rows.column_names())?;
+    }
+    while let Some(row) = rows.next_row()? {
+        writer.write(&row)?;
+    }
+    let count = writer.finish()?;
+
+    Ok(CsvSummary {
+        rows: count,
+        columns: rows.columns.len(),
+    })
+}
+
+// writes each row as its own json file in opts.output_dir, returning the paths in row order
+pub fn process_csv_split(opts: &CsvOpts) -> Result<Vec<PathBuf>, RcliError> {
+    Ok(split_csv(opts)?)
+}
+
+fn split_csv(opts: &CsvOpts) -> Result<Vec<PathBuf>> {
+    let Some(dir) = &opts.output_dir else {
+        return Err(anyhow!("--split needs --output-dir"));
+    };
+    if !matches!(opts.output_format()?, OutputFormat::Json) {
+        return Err(anyhow!("--split only writes json files"));
+    }
+
+    let input = open_input(&opts.input, opts.progress, opts.timeout)?;
+    let mut rows = Rows::open(input, opts)?;
+    let name_column = match &opts.name_column {
+        Some(name) => match rows.headers.iter().position(|h| h == name) {
+            Some(i) => Some((i, name)),
+            None => return Err(anyhow!("Name column not found: {}", name)),
+        },
+        None => None,
+    };
+
+    let mut names = HashSet::new();
+    let mut written = Vec::new();
+    while let Some(row) = rows.next_row()? {
+        let name = match name_column {
+            Some((i, column)) => row_file_name(column, &rows.record[i])?,
+            None => format!("row-{:04}", rows.count),
+        };
+        if !names.insert(name.clone()) {
+            let column = name_column.map_or("row", |(_, column)| column);
+            return Err(anyhow!(
+                "{} value {:?} is not unique, row files would collide",
+                column,
+                name
+            ));
+        }
+        let path = dir.join(format!("{}.json", name));
+        check_overwrite(&path, opts.force)?;
+        let mut json = if !opts.compact {
+            serde_json::to_string_pretty(&row)?
+        } else {
+            serde_json::to_string(&row)?
+        };
+        json.push('\n');
+        fs::write(&path, json)?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+// the value becomes <value>.json, so it can't point outside the output directory
+fn row_file_name(column: &str, value: &str) -> Result<String> {
+    if value.is_empty() || value == "." || value == ".." || value.contains(['/', '\\', '\0']) {
+        return Err(anyhow!(
+            "{} value {:?} can't be used as a file name",
+            column,
+            value
+        ));
+    }
+    Ok(value.to_string())
+}
+
+// the rows that pass --filter, --skip and --limit, projected to the selected columns
+struct Rows<'a, 'o> {
+    reader: Reader<Box<dyn BufRead + 'a>>,
+    headers: StringRecord,
+    columns: Vec<usize>,
+    filters: Vec<(usize, &'o str)>,
+    opts: &'o CsvOpts,
+    // the record behind the last row returned
+    record: StringRecord,
+    skipped: usize,
+    count: usize,
+}
+
+impl<'a, 'o> Rows<'a, 'o> {
+    fn open(input: impl Read + 'a, opts: &'o CsvOpts) -> Result<Self> {
+        let (reader, headers) = open_csv(input, opts)?;
+        let columns = select_columns(&headers, &requested_columns(opts)?)?;
+        let filters = resolve_filters(&headers, &opts.filter)?;
+        Ok(Rows {
+            reader,
+            headers,
+            columns,
+            filters,
+            opts,
+            record: StringRecord::new(),
+            skipped: 0,
+            count: 0,
+        })
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        self.columns
+            .iter()
+            .map(|&i| self.headers[i].to_string())
+            .collect()
+    }
+
+    fn next_row(&mut self) -> Result<Option<Value>> {
+        while self.opts.limit.is_none_or(|limit| self.count < limit)
+            && read_record(&mut self.reader, &mut self.record)?
+        {
+            if !self
+                .filters
+                .iter()
+                .all(|&(i, value)| &self.record[i] == value)
+            {
+                continue;
+            }
+            if self.skipped < self.opts.skip {
+                self.skipped += 1;
+                continue;
+            }
+            self.count += 1;
+            return Ok(Some(self.row_value()));
+        }
+        Ok(None)
+    }
+
+    fn row_value(&self) -> Value {
+        let cell = |i: usize| {
+            if self
+                .opts
+                .null_value
+                .iter()
+                .any(|null| null == &self.record[i])
+            {
+                Value::Null
+            } else {
+                Value::from(&self.record[i])
+            }
+        };
+        if self.opts.raw_headers {
+            // positional, so repeated header names are kept
+            self.columns.iter().map(|&i| cell(i)).collect()
+        } else {
+            // serde_json's preserve_order feature keeps the keys in column order
+            self.columns
+                .iter()
+                .map(|&i| (&self.headers[i], cell(i)))
+                .collect()
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MalformedRow {
+    pub line: u64,
+    pub expected: usize,
+    pub found: usize,
+}
+
+impl fmt::Display for MalformedRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}: expected {} fields, found {}",
+            self.line, self.expected, self.found
+        )
+    }
+}
+
+pub fn process_csv_validate(opts: &CsvOpts) -> Result<Vec<MalformedRow>, RcliError> {
+    let input = open_input(&opts.input, opts.progress, opts.timeout)?;
+    let (mut reader, headers) = open_csv(input, opts)?;
+    let mut malformed = Vec::new();
+    let mut record = StringRecord::new();
+    while read_record(&mut reader, &mut record)? {
+        if record.len() != headers.len() {
+            malformed.push(MalformedRow {
+                line: record.position().map_or(0, |p| p.line()),
+                expected: headers.len(),
+                found: record.len(),
+            });
+        }
+    }
+
+    Ok(malformed)
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CsvStats {
+    pub rows: usize,
+    pub columns: Vec<String>,
+    // non-empty cells of each column, in column order
+    pub non_empty: Vec<usize>,
+}
+
+// streams the whole input, only the counters are kept in memory
+pub fn process_csv_stats(opts: &CsvOpts) -> Result<CsvStats, RcliError> {
+    let input = open_input(&opts.input, opts.progress, opts.timeout)?;
+    let (mut reader, headers) = open_csv(input, opts)?;
+    let mut stats = CsvStats {
+        rows: 0,
+        columns: headers.iter().map(String::from).collect(),
+        non_empty: vec![0; headers.len()],
+    };
+    let mut record = StringRecord::new();
+    while read_record(&mut reader, &mut record)? {
+        stats.rows += 1;
+        for (count, cell) in stats.non_empty.iter_mut().zip(record.iter()) {
+            if !cell.is_empty() {
+                *count += 1;
+            }
+        }
+    }
+
+    Ok(stats)
+}
+
+// the csv error is prefixed with the line the failing record starts on
+fn read_record(reader: &mut Reader<impl Read>, record: &mut StringRecord) -> Result<bool> {
+    reader.read_record(record).map_err(|e| {
+        let line = e
+            .position()
+            .map_or_else(|| reader.position().line(), |p| p.line());
+        RcliError::CsvRecord { line, source: e }.into()
+    })
+}
+
+// the reader is flexible in validate mode so that short/long rows can be reported
+fn open_csv<'a>(
+    input: impl Read + 'a,
+    opts: &CsvOpts,
+) -> Result<(Reader<Box<dyn BufRead + 'a>>, StringRecord)> {
+    let mut input = decompress(input)?;
+    let delimiter = match opts.delimiter {
+        Delimiter::Char(delimiter) => delimiter,
+        Delimiter::Auto => {
+            let delimiter = sniff_delimiter(input.fill_buf()?);
+            eprintln!("Detected delimiter: {:?}", delimiter as char);
+            delimiter
+        }
+    };
+    let mut reader = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .quote(opts.quote)
+        .quoting(!opts.no_quoting)
+        .escape(opts.escape)
+        .has_headers(!opts.no_header)
+        .flexible(opts.validate)
+        .trim(if opts.trim { Trim::All } else { Trim::None })
+        .from_reader(input);
+    let headers = if opts.no_header {
+        generate_headers(reader.headers()?.len())
+    } else {
+        reader.headers()?.clone()
+    };
+
+    Ok((reader, headers))
+}
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// file, stdin or URL
+fn open_input(input: &str, progress: bool, timeout: u64) -> Result<Box<dyn Read>> {
+    let reader = if is_url(input) {
+        fetch_url(input, timeout)?
+    } else {
+        get_reader(input)?
+    };
+    if progress {
+        return Ok(Box::new(progress_bar(input)?.wrap_read(reader)));
+    }
+    Ok(reader)
+}
+
+#[cfg(feature = "url")]
+fn fetch_url(url: &str, timeout: u64) -> Result<Box<dyn Read>> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(timeout))
+        .build()?;
+    let response = client.get(url).send()?;
+    if !response.status().is_success() {
+        return Err(anyhow!("GET {} returned {}", url, response.status()));
+    }
+    Ok(Box::new(response))
+}
+
+#[cfg(not(feature = "url"))]
+fn fetch_url(url: &str, _timeout: u64) -> Result<Box<dyn Read>> {
+    Err(anyhow!(
+        "cannot read {}: rcli was built without the `url` feature",
+        url
+    ))
+}
+
+// transparently decompressed when it starts with the gzip magic bytes
+fn decompress<'a>(input: impl Read + 'a) -> Result<Box<dyn BufRead + 'a>> {
+    let mut reader = BufReader::new(input);
+    if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        return Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))));
+    }
+    Ok(Box::new(reader))
+}
+
+// drawn on stderr and driven by raw input bytes, so gzip input counts compressed bytes;
+// stdin and URLs have no known length and get a spinner instead of a bar
+fn progress_bar(input: &str) -> Result<ProgressBar> {
+    let bar = if input == "-" || is_url(input) {
+        ProgressBar::new_spinner()
+            .with_style(ProgressStyle::with_template("{spinner} {bytes} read")?)
+    } else {
+        ProgressBar::new(fs::metadata(input)?.len()).with_style(ProgressStyle::with_template(
+            "{bar:40} {bytes}/{total_bytes} ({eta})",
+        )?)
+    };
+    Ok(bar.with_finish(ProgressFinish::AndClear))
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const DELIMITER_CANDIDATES: [u8; 4] = [b',', b';', b'\t', b'|'];
+
+// pick the most frequent candidate on the first line, comma wins ties
+fn sniff_delimiter(buf: &[u8]) -> u8 {
+    let line = buf.split(|&b| b == b'\n').next().unwrap_or_default();
+    let mut best = (b',', 0);
+    for candidate in DELIMITER_CANDIDATES {
+        let count = line.iter().filter(|&&b| b == candidate).count();
+        if count > best.1 {
+            best = (candidate, count);
+        }
+    }
+    best.0
+}
+
+// col1, col2, ... zero-padded to the widest index so the keys sort correctly
+fn generate_headers(len: usize) -> StringRecord {
+    let width = len.to_string().len();
+    (1..=len).map(|i| format!("col{:0width$}", i)).collect()
+}
+
+// --columns followed by the lines of --columns-file, names already listed are not repeated
+fn requested_columns(opts: &CsvOpts) -> Result<Vec<String>> {
+    let mut columns = opts.columns.clone();
+    if let Some(path) = &opts.columns_file {
+        for column in get_buf(path)?.lines() {
+            if !column.is_empty() && !columns.iter().any(|c| c == column) {
+                columns.push(column.to_string());
+            }
+        }
+    }
+    Ok(columns)
+}
+
+// map the requested column names to header indices, keeping the requested order
+fn select_columns(headers: &StringRecord, columns: &[String]) -> Result<Vec<usize>> {
+    if columns.is_empty() {
+        return Ok((0..headers.len()).collect());
+    }
+
+    let mut indices = Vec::with_capacity(columns.len());
+    let mut missing = Vec::new();
+    for column in columns {
+        match headers.iter().position(|h| h == column) {
+            Some(i) => indices.push(i),
+            None => missing.push(column.as_str()),
+        }
+    }
+    if !missing.is_empty() {
+        return Err(anyhow!("Columns not found: {}", missing.join(", ")));
+    }
+
+    Ok(indices)
+}
+
+// map filter keys to header indices, erroring early on unknown columns
+fn resolve_filters<'a>(
+    headers: &StringRecord,
+    filters: &'a [(String, String)],
+) -> Result<Vec<(usize, &'a str)>> {
+    filters
+        .iter()
+        .map(|(key, value)| match headers.iter().position(|h| h == key) {
+            Some(i) => Ok((i, value.as_str())),
+            None => Err(anyhow!("Filter column not found: {}", key)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use clap::Parser;
+    use std::{
+        collections::BTreeMap,
+        fs::{self, File},
+        io::BufWriter,
+        path::{Path, PathBuf},
+    };
+
+    // id,name with ids 1..=rows
+    fn numbered_csv(dir: &Path, rows: usize) -> Result<PathBuf> {
+        let input = dir.join("numbered.csv");
+        let mut file = BufWriter::new(File::create(&input)?);
+        writeln!(file, "id,name")?;
+        for i in 1..=rows {
+            writeln!(file, "{},name-{}", i, i)?;
+        }
+        file.flush()?;
+        Ok(input)
+    }
+
+    fn convert_to_json(args: &[&str]) -> Result<Vec<Value>> {
+        let opts = CsvOpts::parse_from(std::iter::once("csv").chain(args.iter().copied()));
+        let mut buf = Vec::new();
+        convert_csv(&opts, &mut buf)?;
+        Ok(serde_json::from_slice(&buf)?)
+    }
+
+    #[test]
+    fn test_process_csv_yaml() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let output = dir.path().join("output.yaml");
+        let opts = CsvOpts::parse_from(["csv", "-i", "fixtures/players.csv", "--format", "yaml"]);
+        process_csv(&opts, output.display().to_string())?;
+
+        let content = fs::read_to_string(output)?;
+        let records: Vec<BTreeMap<String, String>> = serde_yaml::from_str(&content)?;
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["Name"], "Wojciech Szczesny");
+        assert_eq!(records[1]["DOB"], "Nov 10, 1992 (26)");
+        assert_eq!(records[1]["Kit Number"], "37");
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_csv_base64() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let output = dir.path().join("output.b64");
+        let opts = CsvOpts::parse_from([
+            "csv",
+            "-i",
+            "fixtures/players.csv",
+            "--format",
+            "json",
+            "--base64",
+        ]);
+        process_csv(&opts, output.display().to_string())?;
+
+        let encoded = fs::read_to_string(output)?;
+        let decoded = STANDARD.decode(encoded.trim_end())?;
+        let records: Vec<Value> = serde_json::from_slice(&decoded)?;
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["Name"], "Wojciech Szczesny");
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_csv_toml() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let output = dir.path().join("output.toml");
+        let opts = CsvOpts::parse_from(["csv", "-i", "fixtures/players.csv", "--format", "toml"]);
+        process_csv(&opts, output.display().to_string())?;
+
+        let content = fs::read_to_string(output)?;
+        assert_eq!(content.matches("[[records]]").count(), 2);
+        let doc: toml::Table = content.parse()?;
+        let records = doc["records"]
+            .as_array()
+            .expect("records should be an array");
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["Nationality"].as_str(), Some("Poland"));
+        assert_eq!(records[1]["Kit Number"].as_str(), Some("37"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_csv_semicolon_delimiter() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let output = dir.path().join("output.json");
+        let opts = CsvOpts::parse_from(["csv", "-i", "fixtures/players-semicolon.csv", "-d", ";"]);
+        process_csv(&opts, output.display().to_string())?;
+
+        let content = fs::read_to_string(output)?;
+        let records: Vec<BTreeMap<String, String>> = serde_json::from_str(&content)?;
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["Name"], "Wojciech Szczesny");
+        assert_eq!(records[0]["DOB"], "Apr 18, 1990 (29)");
+        assert_eq!(records[1]["Kit Number"], "37");
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_csv_no_header() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let output = dir.path().join("output.json");
+        let opts = CsvOpts::parse_from(["csv", "-i", "fixtures/no-header.csv", "--no-header"]);
+        process_csv(&opts, output.display().to_string())?;
+
+        let content = fs::read_to_string(output)?;
+        let records: Vec<BTreeMap<String, String>> = serde_json::from_str(&content)?;
+        assert_eq!(records.len(), 2);
+        let keys: Vec<_> = records[0].keys().map(String::as_str).collect();
+        assert_eq!(keys, ["col1", "col2", "col3"]);
+        assert_eq!(records[0]["col1"], "Wojciech Szczesny");
+        assert_eq!(records[1]["col3"], "37");
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_headers() {
+        assert_eq!(generate_headers(3), vec!["col1", "col2", "col3"]);
+        let headers = generate_headers(12);
+        assert_eq!(&headers[0], "col01");
+        assert_eq!(&headers[9], "col10");
+        assert_eq!(&headers[11], "col12");
+    }
+
+    #[test]
+    fn test_process_csv_columns() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let output = dir.path().join("output.json");
+        let opts = CsvOpts::parse_from([
+            "csv",
+            "-i",
+            "fixtures/players.csv",
+            "--columns",
+            "Kit Number,Name",
+        ]);
+        process_csv(&opts, output.display().to_string())?;
+
+        let content = fs::read_to_string(output)?;
+        let records: Vec<Value> = serde_json::from_str(&content)?;
+        assert_eq!(records.len(), 2);
+        let keys: Vec<_> = records[0]
+            .as_object()
+            .expect("record should be an object")
+            .keys()
+            .collect();
+        assert_eq!(keys, ["Kit Number", "Name"]);
+        assert_eq!(records[1]["Name"], "Mattia Perin");
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_csv_columns_file() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let columns = dir.path().join("cols.txt");
+        fs::write(&columns, "Position\n\nName\n")?;
+        let columns = columns.to_str().expect("temp path should be utf-8");
+
+        let records = convert_to_json(&["-i", "fixtures/players.csv", "--columns-file", columns])?;
+        let keys: Vec<_> = records[0].as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["Position", "Name"]);
+
+        // inline columns come first, duplicates are dropped
+        let records = convert_to_json(&[
+            "-i",
+            "fixtures/players.csv",
+            "--columns",
+            "Name,Kit Number",
+            "--columns-file",
+            columns,
+        ])?;
+        let keys: Vec<_> = records[0].as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["Name", "Kit Number", "Position"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_select_columns_missing() {
+        let headers = StringRecord::from(vec!["Name", "Position"]);
+        let columns = vec!["Name".to_string(), "Age".to_string(), "Club".to_string()];
+        let err = select_columns(&headers, &columns).unwrap_err();
+        assert_eq!(err.to_string(), "Columns not found: Age, Club");
+    }
+
+    #[test]
+    fn test_process_csv_streams_large_file() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let input = numbered_csv(dir.path(), 100_000)?;
+
+        let output = dir.path().join("output.json");
+        let input = input.display().to_string();
+        let opts = CsvOpts::parse_from(["csv", "-i", &input, "--compact"]);
+        process_csv(&opts, output.display().to_string())?;
+
+        let content = fs::read_to_string(output)?;
+        // --compact: one record per line between the brackets
+        assert_eq!(content.lines().count(), 100_000 + 2);
+        let records: Vec<Value> = serde_json::from_str(&content)?;
+        assert_eq!(records.len(), 100_000);
+        assert_eq!(records[99_999]["name"], "name-100000");
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert_csv_to_writer() -> Result<()> {
+        let opts = CsvOpts::parse_from(["csv", "-i", "fixtures/players.csv", "-o", "-"]);
+        let mut buf = Vec::new();
+        convert_csv(&opts, &mut buf)?;
+
+        let records: Vec<Value> = serde_json::from_slice(&buf)?;
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["Name"], "Wojciech Szczesny");
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_csv_filter() -> Result<()> {
+        let opts = CsvOpts::parse_from([
+            "csv",
+            "-i",
+            "fixtures/players.csv",
+            "--filter",
+            "Nationality=Italy",
+        ]);
+        let mut buf = Vec::new();
+        convert_csv(&opts, &mut buf)?;
+
+        let records: Vec<Value> = serde_json::from_slice(&buf)?;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["Name"], "Mattia Perin");
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_csv_multiple_filters() -> Result<()> {
+        let opts = CsvOpts::parse_from([
+            "csv",
+            "-i",
+            "fixtures/players.csv",
+            "--filter",
+            "Position=Goalkeeper",
+            "--filter",
+            "Kit Number=1",
+        ]);
+        let mut buf = Vec::new();
+        convert_csv(&opts, &mut buf)?;
+
+        let records: Vec<Value> = serde_json::from_slice(&buf)?;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["Name"], "Wojciech Szczesny");
+
+        let opts = CsvOpts::parse_from([
+            "csv",
+            "-i",
+            "fixtures/players.csv",
+            "--filter",
+            "Position=Goalkeeper",
+            "--filter",
+            "Nationality=Spain",
+        ]);
+        let mut buf = Vec::new();
+        convert_csv(&opts, &mut buf)?;
+        let records: Vec<Value> = serde_json::from_slice(&buf)?;
+        assert!(records.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_csv_filter_unknown_column() {
+        let opts = CsvOpts::parse_from([
+            "csv",
+            "-i",
+            "fixtures/players.csv",
+            "--filter",
+            "Club=Juventus",
+        ]);
+        let err = convert_csv(&opts, Vec::new()).unwrap_err();
+        assert_eq!(err.to_string(), "Filter column not found: Club");
+    }
+
+    #[test]
+    fn test_process_csv_skip_limit() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let input = numbered_csv(dir.path(), 200)?;
+        let input = input.to_str().expect("temp path should be utf-8");
+
+        let records = convert_to_json(&["-i", input, "--skip", "100", "--limit", "10"])?;
+        assert_eq!(records.len(), 10);
+        assert_eq!(records[0]["id"], "101");
+        assert_eq!(records[9]["id"], "110");
+
+        let records = convert_to_json(&["-i", input, "--limit", "0"])?;
+        assert!(records.is_empty());
+
+        let records = convert_to_json(&["-i", input, "--skip", "195"])?;
+        assert_eq!(records.len(), 5);
+        assert_eq!(records[0]["id"], "196");
+
+        let records = convert_to_json(&["-i", input, "--skip", "200"])?;
+        assert!(records.is_empty());
+
+        let records = convert_to_json(&["-i", input, "--limit", "1000"])?;
+        assert_eq!(records.len(), 200);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sniff_delimiter() -> Result<()> {
+        assert_eq!(sniff_delimiter(&fs::read("fixtures/players.csv")?), b',');
+        assert_eq!(
+            sniff_delimiter(&fs::read("fixtures/players-semicolon.csv")?),
+            b';'
+        );
+        assert_eq!(
+            sniff_delimiter(&fs::read("fixtures/players-tab.csv")?),
+            b'\t'
+        );
+        assert_eq!(sniff_delimiter(b"a|b|c\n1,2|3|4"), b'|');
+        assert_eq!(sniff_delimiter(b"single"), b',');
+        assert_eq!(sniff_delimiter(b""), b',');
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_csv_auto_delimiter() -> Result<()> {
+        for input in [
+            "fixtures/players.csv",
+            "fixtures/players-semicolon.csv",
+            "fixtures/players-tab.csv",
+        ] {
+            let records = convert_to_json(&["-i", input, "-d", "auto"])?;
+            assert_eq!(records.len(), 2);
+            assert_eq!(records[0]["DOB"], "Apr 18, 1990 (29)");
+            assert_eq!(records[1]["Kit Number"], "37");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_csv_ndjson() -> Result<()> {
+        let opts = CsvOpts::parse_from(["csv", "-i", "fixtures/players.csv", "--format", "ndjson"]);
+        let mut buf = Vec::new();
+        convert_csv(&opts, &mut buf)?;
+
+        let content = String::from_utf8(buf)?;
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        for line in lines {
+            let record: Value = serde_json::from_str(line)?;
+            assert!(record.is_object());
+        }
+        assert!(content.ends_with("\"Kit Number\":\"37\"}\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_csv_pretty() -> Result<()> {
+        let opts = CsvOpts::parse_from(["csv", "-i", "fixtures/players.csv", "--compact"]);
+        let mut compact = Vec::new();
+        convert_csv(&opts, &mut compact)?;
+
+        // pretty is the default, --pretty only spells it out
+        let opts = CsvOpts::parse_from(["csv", "-i", "fixtures/players.csv"]);
+        let mut pretty = Vec::new();
+        convert_csv(&opts, &mut pretty)?;
+        let opts = CsvOpts::parse_from(["csv", "-i", "fixtures/players.csv", "--pretty"]);
+        let mut explicit = Vec::new();
+        convert_csv(&opts, &mut explicit)?;
+        assert_eq!(pretty, explicit);
+
+        assert!(pretty.len() > compact.len());
+        let compact: Value = serde_json::from_slice(&compact)?;
+        let pretty_value: Value = serde_json::from_slice(&pretty)?;
+        assert_eq!(compact, pretty_value);
+        assert_eq!(
+            String::from_utf8(pretty)?,
+            serde_json::to_string_pretty(&compact)? + "\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_csv_pretty_ndjson() {
+        let opts = CsvOpts::parse_from([
+            "csv",
+            "-i",
+            "fixtures/players.csv",
+            "--format",
+            "ndjson",
+            "--pretty",
+        ]);
+        let err = convert_csv(&opts, Vec::new()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "--pretty cannot be used with ndjson output"
+        );
+    }
+
+    #[test]
+    fn test_process_csv_xml() -> Result<()> {
+        let opts = CsvOpts::parse_from(["csv", "-i", "fixtures/special.csv", "--format", "xml"]);
+        let mut buf = Vec::new();
+        convert_csv(&opts, &mut buf)?;
+
+        let content = String::from_utf8(buf)?;
+        assert!(content.contains("<field name=\"note\">&lt;b&gt;bold&lt;/b&gt; &amp; more</field>"));
+
+        let doc = roxmltree::Document::parse(&content)?;
+        let records: Vec<_> = doc
+            .root_element()
+            .children()
+            .filter(|n| n.is_element())
+            .collect();
+        assert_eq!(records.len(), 2);
+        let fields: Vec<_> = records[0].children().collect();
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].attribute("name"), Some("name"));
+        assert_eq!(fields[0].text(), Some("Tom & Jerry"));
+        assert_eq!(fields[1].text(), Some("<b>bold</b> & more"));
+        assert_eq!(
+            records[1].children().nth(1).and_then(|n| n.text()),
+            Some("x > y")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_csv_gzip() -> Result<()> {
+        let records = convert_to_json(&["-i", "fixtures/players.csv.gz"])?;
+        assert_eq!(records, convert_to_json(&["-i", "fixtures/players.csv"])?);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1]["Name"], "Mattia Perin");
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert_csv_progress_keeps_output() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let input = numbered_csv(dir.path(), 1000)?;
+        let input = input.to_str().unwrap();
+
+        for input in [input, "fixtures/players.csv.gz"] {
+            let mut plain = Vec::new();
+            convert_csv(&CsvOpts::parse_from(["csv", "-i", input]), &mut plain)?;
+            let mut progress = Vec::new();
+            let opts = CsvOpts::parse_from(["csv", "-i", input, "--progress"]);
+            convert_csv(&opts, &mut progress)?;
+            assert_eq!(plain, progress);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_to_json_in_memory() -> Result<()> {
+        let opts = CsvOpts::parse_from(["csv", "-i", "-"]);
+        let mut buf = Vec::new();
+        let summary = csv_to_json("name,age\nalice,30\nbob,25\n".as_bytes(), &mut buf, &opts)?;
+        assert_eq!(
+            summary,
+            CsvSummary {
+                rows: 2,
+                columns: 2
+            }
+        );
+
+        let records: Vec<Value> = serde_json::from_slice(&buf)?;
+        assert_eq!(
+            records,
+            [
+                serde_json::json!({"name": "alice", "age": "30"}),
+                serde_json::json!({"name": "bob", "age": "25"}),
+            ]
+        );
+        Ok(())
+    }
+
+    #[cfg(not(feature = "url"))]
+    #[test]
+    fn test_url_input_needs_feature() {
+        let opts = CsvOpts::parse_from(["csv", "-i", "http://127.0.0.1:1/data.csv"]);
+        let err = convert_csv(&opts, Vec::new()).unwrap_err();
+        assert!(err.to_string().contains("`url` feature"));
+    }
+
+    #[cfg(feature = "url")]
+    mod url {
+        use super::*;
+        use std::{
+            io::{BufRead, BufReader},
+            net::TcpListener,
+            thread,
+        };
+
+        // answers a single request with the given status line and body
+        fn serve_once(status: &'static str, body: &'static str) -> String {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap();
+            thread::spawn(move || {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            });
+            format!("http://{}/data.csv", addr)
+        }
+
+        #[test]
+        fn test_csv_from_url() -> Result<()> {
+            let url = serve_once("200 OK", "name,age\nalice,30\n");
+            let records = convert_to_json(&["-i", &url])?;
+            assert_eq!(records.len(), 1);
+            assert_eq!(records[0]["name"], "alice");
+            Ok(())
+        }
+
+        #[test]
+        fn test_csv_from_url_not_found() {
+            let url = serve_once("404 Not Found", "missing");
+            let err = convert_to_json(&["-i", &url]).unwrap_err();
+            assert!(err.to_string().contains("404"));
+        }
+    }
+
+    #[test]
+    fn test_csv_to_json_raw_headers() -> Result<()> {
+        let input = "id,value,value\n1,a,b\n2,c,d\n";
+        let opts = CsvOpts::parse_from(["csv", "-i", "-", "--raw-headers"]);
+        let mut buf = Vec::new();
+        csv_to_json(input.as_bytes(), &mut buf, &opts)?;
+        let json: Value = serde_json::from_slice(&buf)?;
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "headers": ["id", "value", "value"],
+                "rows": [["1", "a", "b"], ["2", "c", "d"]],
+            })
+        );
+
+        let opts = CsvOpts::parse_from(["csv", "-i", "-", "--raw-headers", "--format", "ndjson"]);
+        let mut buf = Vec::new();
+        csv_to_json(input.as_bytes(), &mut buf, &opts)?;
+        assert_eq!(
+            String::from_utf8(buf)?,
+            "[\"id\",\"value\",\"value\"]\n[\"1\",\"a\",\"b\"]\n[\"2\",\"c\",\"d\"]\n"
+        );
+
+        let opts = CsvOpts::parse_from(["csv", "-i", "-", "--raw-headers", "--limit", "0"]);
+        let mut buf = Vec::new();
+        csv_to_json(input.as_bytes(), &mut buf, &opts)?;
+        let json: Value = serde_json::from_slice(&buf)?;
+        assert_eq!(json["rows"], serde_json::json!([]));
+
+        let opts = CsvOpts::parse_from(["csv", "-i", "-", "--raw-headers", "--format", "yaml"]);
+        assert!(csv_to_json(input.as_bytes(), Vec::new(), &opts).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_to_json_error_has_line() {
+        // the stray quote splits the third line into three fields
+        let input = "id,name\n1,ok\n2,\"bad\"quote\",x\n3,ok\n";
+        let opts = CsvOpts::parse_from(["csv", "-i", "-"]);
+        let err = csv_to_json(input.as_bytes(), Vec::new(), &opts).unwrap_err();
+        assert!(
+            err.to_string().starts_with("error at line 3: "),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_csv_to_json_null_value() -> Result<()> {
+        let input = "id,score\n1,NA\n2,\n3,N/A\n";
+        let opts =
+            CsvOpts::parse_from(["csv", "-i", "-", "--null-value", "NA", "--null-value", ""]);
+        let mut buf = Vec::new();
+        csv_to_json(input.as_bytes(), &mut buf, &opts)?;
+        let rows: Vec<Value> = serde_json::from_slice(&buf)?;
+        assert_eq!(rows[0]["score"], Value::Null);
+        assert_eq!(rows[1]["score"], Value::Null);
+        assert_eq!(rows[2]["score"], "N/A");
+
+        let opts =
+            CsvOpts::parse_from(["csv", "-i", "-", "--null-value", "NA", "--format", "toml"]);
+        let mut buf = Vec::new();
+        csv_to_json(input.as_bytes(), &mut buf, &opts)?;
+        let toml: toml::Value = toml::from_str(&String::from_utf8(buf)?)?;
+        assert!(toml["records"][0].get("score").is_none());
+        assert_eq!(toml["records"][1]["score"].as_str(), Some(""));
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_csv_trim() -> Result<()> {
+        let records = convert_to_json(&["-i", "fixtures/padded.csv"])?;
+        assert_eq!(records[0]["Name "], "  Mattia Perin  ");
+        assert_eq!(records[0][" Nationality"], " Italy ");
+
+        let records = convert_to_json(&["-i", "fixtures/padded.csv", "--trim"])?;
+        assert_eq!(records[0]["Name"], "Mattia Perin");
+        assert_eq!(records[0]["Nationality"], "Italy");
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert_csv_summary() -> Result<()> {
+        let opts = CsvOpts::parse_from(["csv", "-i", "fixtures/players.csv"]);
+        let summary = convert_csv(&opts, Vec::new())?;
+        assert_eq!(
+            summary,
+            CsvSummary {
+                rows: 2,
+                columns: 5
+            }
+        );
+        assert_eq!(summary.to_string(), "Converted 2 rows, 5 columns");
+
+        let opts = CsvOpts::parse_from([
+            "csv",
+            "-i",
+            "fixtures/players.csv",
+            "--columns",
+            "Name",
+            "--limit",
+            "1",
+        ]);
+        let summary = convert_csv(&opts, Vec::new())?;
+        assert_eq!(
+            summary,
+            CsvSummary {
+                rows: 1,
+                columns: 1
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_csv_validate() -> Result<()> {
+        let opts = CsvOpts::parse_from(["csv", "-i", "fixtures/malformed.csv", "--validate"]);
+        let malformed = process_csv_validate(&opts)?;
+        assert_eq!(
+            malformed,
+            vec![MalformedRow {
+                line: 3,
+                expected: 3,
+                found: 2
+            }]
+        );
+        assert_eq!(
+            malformed[0].to_string(),
+            "line 3: expected 3 fields, found 2"
+        );
+
+        let opts = CsvOpts::parse_from(["csv", "-i", "fixtures/players.csv", "--validate"]);
+        assert!(process_csv_validate(&opts)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_csv_stats() -> Result<()> {
+        let opts = CsvOpts::parse_from(["csv", "-i", "fixtures/sparse.csv", "--stats"]);
+        let stats = process_csv_stats(&opts)?;
+        assert_eq!(
+            serde_json::to_value(&stats)?,
+            serde_json::json!({
+                "rows": 3,
+                "columns": ["id", "email", "phone"],
+                "non_empty": [3, 2, 1],
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_to_json_custom_quote() -> Result<()> {
+        let opts = CsvOpts::parse_from([
+            "csv",
+            "-i",
+            "fixtures/backtick.csv",
+            "-d",
+            "|",
+            "--quote",
+            "`",
+        ]);
+        let mut buf = Vec::new();
+        csv_to_json(File::open(&opts.input)?, &mut buf, &opts)?;
+        let records: Vec<Value> = serde_json::from_slice(&buf)?;
+        assert_eq!(
+            records,
+            [
+                serde_json::json!({"name": "Perin| Mattia", "note": "goalkeeper"}),
+                serde_json::json!({"name": "Szczesny", "note": "saves|clean sheets"}),
+            ]
+        );
+
+        // with quoting off the default '"' is ordinary data
+        let opts = CsvOpts::parse_from(["csv", "-i", "-", "--no-quoting"]);
+        let mut buf = Vec::new();
+        csv_to_json("name,note\n\"a\",b\n".as_bytes(), &mut buf, &opts)?;
+        let records: Vec<Value> = serde_json::from_slice(&buf)?;
+        assert_eq!(records, [serde_json::json!({"name": "\"a\"", "note": "b"})]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_to_json_multiline_and_escape() -> Result<()> {
+        let opts = CsvOpts::parse_from(["csv", "-i", "fixtures/escaped.csv", "--escape", "\\"]);
+        let mut buf = Vec::new();
+        let summary = csv_to_json(File::open(&opts.input)?, &mut buf, &opts)?;
+        assert_eq!(summary.rows, 2);
+        let records: Vec<Value> = serde_json::from_slice(&buf)?;
+        assert_eq!(
+            records,
+            [
+                serde_json::json!({"name": "Perin", "bio": "Italian keeper\nborn in Latina"}),
+                serde_json::json!({"name": "Szczesny", "bio": "nicknamed \"Tek\""}),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_to_json_keeps_header_order() -> Result<()> {
+        // the header is deliberately not in alphabetical order
+        let header = ["Name", "Position", "DOB", "Nationality", "Kit Number"];
+        let opts = CsvOpts::parse_from(["csv", "-i", "fixtures/players.csv", "--compact"]);
+        let mut buf = Vec::new();
+        csv_to_json(File::open(&opts.input)?, &mut buf, &opts)?;
+        let json = String::from_utf8(buf)?;
+        assert!(json.starts_with(
+            "[\n{\"Name\":\"Wojciech Szczesny\",\"Position\":\"Goalkeeper\",\"DOB\":"
+        ));
+        let records: Vec<Value> = serde_json::from_str(&json)?;
+        for record in &records {
+            let keys: Vec<_> = record.as_object().unwrap().keys().collect();
+            assert_eq!(keys, header);
+        }
+
+        let opts = CsvOpts::parse_from(["csv", "-i", "fixtures/players.csv", "--format", "yaml"]);
+        let mut buf = Vec::new();
+        csv_to_json(File::open(&opts.input)?, &mut buf, &opts)?;
+        let yaml = String::from_utf8(buf)?;
+        let keys: Vec<_> = yaml
+            .lines()
+            .take(header.len())
+            .map(|line| line.trim_start_matches("- ").trim_start())
+            .map(|line| line.split(':').next().unwrap())
+            .collect();
+        assert_eq!(keys, header);
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_csv_split() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let out = dir.path().to_str().unwrap();
+        let opts = CsvOpts::parse_from([
+            "csv",
+            "-i",
+            "fixtures/sparse.csv",
+            "--split",
+            "--output-dir",
+            out,
+        ]);
+        let written = process_csv_split(&opts)?;
+        let names: Vec<_> = written
+            .iter()
+            .map(|p| p.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, ["row-0001.json", "row-0002.json", "row-0003.json"]);
+        let row: Value = serde_json::from_str(&fs::read_to_string(&written[1])?)?;
+        assert_eq!(
+            row,
+            serde_json::json!({"id": "2", "email": "", "phone": "555-0100"})
+        );
+
+        // named after a column, existing files need --force
+        let opts = CsvOpts::parse_from([
+            "csv",
+            "-i",
+            "fixtures/sparse.csv",
+            "--split",
+            "--output-dir",
+            out,
+            "--name-column",
+            "id",
+        ]);
+        let written = process_csv_split(&opts)?;
+        assert_eq!(written[2], dir.path().join("3.json"));
+        assert!(process_csv_split(&opts).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_csv_split_name_collision() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let input = dir.path().join("dup.csv");
+        fs::write(&input, "id,name\na,x\nb,y\na,z\n")?;
+        let split = |column: &str| {
+            let opts = CsvOpts::parse_from([
+                "csv",
+                "-i",
+                input.to_str().unwrap(),
+                "--split",
+                "--output-dir",
+                dir.path().to_str().unwrap(),
+                "--name-column",
+                column,
+            ]);
+            process_csv_split(&opts)
+        };
+        let err = split("id").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "id value \"a\" is not unique, row files would collide"
+        );
+
+        fs::write(&input, "id,name\n../escape,x\n")?;
+        assert!(split("id").is_err());
+        assert!(!dir.path().join("../escape.json").exists());
+        assert!(split("missing").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use super::b64::decode_auto;
+use crate::{get_buf_bytes, RcliError};
+use anyhow::Result;
+use serde_json::Value;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DataType {
+    Json,
+    Csv,
+    Base64,
+    Text,
+    Binary,
+}
+
+pub fn process_detect(input: &str) -> Result<DataType, RcliError> {
+    let buf = get_buf_bytes(input)?;
+    Ok(detect(&buf))
+}
+
+// cheapest checks first; the order also breaks ties, e.g. `[1,2]` is json not csv
+fn detect(buf: &[u8]) -> DataType {
+    let text = match std::str::from_utf8(buf) {
+        Ok(text) if !text.contains('\0') => text,
+        _ => return DataType::Binary,
+    };
+    if text.trim().is_empty() {
+        return DataType::Text;
+    }
+    if is_json(text) {
+        DataType::Json
+    } else if is_csv(text) {
+        DataType::Csv
+    } else if is_base64(text) {
+        DataType::Base64
+    } else {
+        DataType::Text
+    }
+}
+
+// a bare number or string is valid json too, but not what anyone means by a json file
+fn is_json(text: &str) -> bool {
+    matches!(
+        serde_json::from_str::<Value>(text),
+        Ok(Value::Object(_) | Value::Array(_))
+    )
+}
+
+// at least two records with the same number (> 1) of fields, quoted commas allowed
+fn is_csv(text: &str) -> bool {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(text.as_bytes());
+    let mut rows = 0;
+    for record in reader.records() {
+        match record {
+            Ok(record) if record.len() > 1 => rows += 1,
+            _ => return false,
+        }
+    }
+    rows > 1
+}
+
+// lines may be wrapped, but a line with spaces in it is prose
+fn is_base64(text: &str) -> bool {
+    if text.lines().any(|l| l.trim().contains(char::is_whitespace)) {
+        return false;
+    }
+    let buf: Vec<u8> = text.bytes().filter(|c| !c.is_ascii_whitespace()).collect();
+    decode_auto(&buf).is_ok()
+}
+
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            DataType::Json => "json",
+            DataType::Csv => "csv",
+            DataType::Base64 => "base64",
+            DataType::Text => "text",
+            DataType::Binary => "binary",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_json() {
+        assert_eq!(detect(br#"{"name": "rcli"}"#), DataType::Json);
+        assert_eq!(detect(b"[1, 2, 3]\n"), DataType::Json);
+        assert_eq!(detect(b"42"), DataType::Text);
+    }
+
+    #[test]
+    fn test_detect_csv() -> Result<()> {
+        let buf = std::fs::read("fixtures/players.csv")?;
+        assert_eq!(detect(&buf), DataType::Csv);
+        assert_eq!(detect(b"a,b\n1,2,3\n"), DataType::Text);
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_base64() -> Result<()> {
+        let buf = std::fs::read("fixtures/binary.b64")?;
+        assert_eq!(detect(&buf), DataType::Base64);
+        assert_eq!(detect(b"aGVsbG8gd29y\nbGQ=\n"), DataType::Base64);
+        assert_eq!(detect(b"Pj4-Pw"), DataType::Base64);
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_text() {
+        assert_eq!(detect(b"hello world\n"), DataType::Text);
+        assert_eq!(detect(b"not base64!"), DataType::Text);
+        assert_eq!(detect(b""), DataType::Text);
+    }
+
+    #[test]
+    fn test_detect_binary() {
+        assert_eq!(detect(&[0x00, 0x9f, 0x92, 0x96, 0xff]), DataType::Binary);
+        assert_eq!(detect(b"abc\0def"), DataType::Binary);
+    }
+}
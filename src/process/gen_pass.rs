@@ -1,46 +1,714 @@
-use anyhow::Result;
-use rand::prelude::SliceRandom;
+use crate::{
+    cli::{GenPassEncoding, GenPassOpts},
+    RcliError,
+};
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rand::{prelude::SliceRandom, rngs::StdRng, Rng, RngCore, SeedableRng};
+use serde::Serialize;
+use zxcvbn::zxcvbn;
 
 const UPPERCASE: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
 const LOWERCASE: &[u8] = b"abcdefghijkmnopqrstuvwxyz";
 const NUMBERS: &[u8] = b"123456789";
 const SYMBOLS: &[u8] = b"!@#$%^&*_";
+// full alphabets including look-alikes such as I/l and O/0
+const UPPERCASE_ALL: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const LOWERCASE_ALL: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const NUMBERS_ALL: &[u8] = b"0123456789";
+// attempts before giving up on reaching GenPassConfig::min_score
+const MAX_SCORE_ATTEMPTS: usize = 100;
+// (name, plural) of each class, in the order process_genpass lists them
+const CLASS_NAMES: [(&str, &str); 4] = [
+    ("uppercase", "uppercase letters"),
+    ("lowercase", "lowercase letters"),
+    ("number", "numbers"),
+    ("symbol", "symbols"),
+];
 
-pub fn process_genpass(
-    length: u8,
-    uppercase: u8,
-    lowercase: u8,
-    number: u8,
-    symbol: u8,
-) -> Result<String> {
-    let mut rng = rand::thread_rng();
-    let mut password = Vec::new();
-    let mut chars = Vec::new();
+#[derive(Debug, Serialize)]
+pub struct PasswordStats {
+    pub score: u8,
+    pub guesses: u64,
+    pub guesses_log10: f64,
+    pub crack_times: CrackTimes,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CrackTimes {
+    pub online_throttling_100_per_hour: String,
+    pub online_no_throttling_10_per_second: String,
+    pub offline_slow_hashing_1e4_per_second: String,
+    pub offline_fast_hashing_1e10_per_second: String,
+}
+
+pub fn process_password_stats(password: &str) -> Result<PasswordStats, RcliError> {
+    let estimate = zxcvbn(password, &[]).map_err(anyhow::Error::from)?;
+    let times = estimate.crack_times();
+    Ok(PasswordStats {
+        score: estimate.score(),
+        guesses: estimate.guesses(),
+        guesses_log10: estimate.guesses_log10(),
+        crack_times: CrackTimes {
+            online_throttling_100_per_hour: times.online_throttling_100_per_hour().to_string(),
+            online_no_throttling_10_per_second: times
+                .online_no_throttling_10_per_second()
+                .to_string(),
+            offline_slow_hashing_1e4_per_second: times
+                .offline_slow_hashing_1e4_per_second()
+                .to_string(),
+            offline_fast_hashing_1e10_per_second: times
+                .offline_fast_hashing_1e10_per_second()
+                .to_string(),
+        },
+    })
+}
 
-    if uppercase == 1 {
-        chars.extend_from_slice(UPPERCASE);
-        password.push(*UPPERCASE.choose(&mut rng).expect("won't be empty"))
+const WORDLIST: &str = include_str!("../../assets/wordlist.txt");
+
+pub fn process_passphrase(
+    words: usize,
+    separator: &str,
+    rng: &mut impl Rng,
+) -> Result<String, RcliError> {
+    if words == 0 {
+        return Err(anyhow!("words must be greater than 0").into());
     }
-    if lowercase == 1 {
-        chars.extend_from_slice(LOWERCASE);
-        password.push(*LOWERCASE.choose(&mut rng).expect("won't be empty"))
+
+    let wordlist: Vec<&str> = WORDLIST.lines().collect();
+    let passphrase = (0..words)
+        .map(|_| *wordlist.choose(rng).expect("won't be empty"))
+        .collect::<Vec<_>>()
+        .join(separator);
+
+    Ok(passphrase)
+}
+
+// `length` random bytes, encoded so they can be printed and used as a key
+pub fn process_genkey(
+    length: usize,
+    encoding: GenPassEncoding,
+    rng: &mut impl Rng,
+) -> Result<String, RcliError> {
+    if length == 0 {
+        return Err(anyhow!("length must be greater than 0").into());
     }
-    if number == 1 {
-        chars.extend_from_slice(NUMBERS);
-        password.push(*NUMBERS.choose(&mut rng).expect("won't be empty"))
+
+    let mut key = vec![0u8; length];
+    rng.fill_bytes(&mut key);
+    match encoding {
+        GenPassEncoding::Base64 => Ok(STANDARD.encode(key)),
+        GenPassEncoding::Hex => Ok(hex::encode(key)),
+        GenPassEncoding::Raw => Err(anyhow!("raw encoding generates a password, not a key").into()),
+    }
+}
+
+// the characters each class draws from, all ASCII
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenPassAlphabets {
+    pub uppercase: String,
+    pub lowercase: String,
+    pub number: String,
+    pub symbol: String,
+}
+
+impl Default for GenPassAlphabets {
+    // the curated sets, without look-alikes such as I/l and O/0
+    fn default() -> Self {
+        GenPassAlphabets::from_bytes(UPPERCASE, LOWERCASE, NUMBERS, SYMBOLS)
+    }
+}
+
+impl GenPassAlphabets {
+    // every letter and digit, including look-alikes
+    pub fn full() -> Self {
+        GenPassAlphabets::from_bytes(UPPERCASE_ALL, LOWERCASE_ALL, NUMBERS_ALL, SYMBOLS)
+    }
+
+    fn from_bytes(uppercase: &[u8], lowercase: &[u8], number: &[u8], symbol: &[u8]) -> Self {
+        let to_string = |set: &[u8]| String::from_utf8_lossy(set).into_owned();
+        GenPassAlphabets {
+            uppercase: to_string(uppercase),
+            lowercase: to_string(lowercase),
+            number: to_string(number),
+            symbol: to_string(symbol),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GenPassConfig {
+    pub length: u8,
+    pub uppercase: bool,
+    pub lowercase: bool,
+    pub number: bool,
+    pub symbol: bool,
+    pub exclude_ambiguous: bool,
+    pub symbols: Option<String>,
+    // minimum number of characters drawn from each enabled class
+    pub min_uppercase: u8,
+    pub min_lowercase: u8,
+    pub min_number: u8,
+    pub min_symbol: u8,
+    // replaces all four sets at once, `exclude_ambiguous` and `symbols` are then ignored
+    pub alphabets: Option<GenPassAlphabets>,
+    // lowest acceptable zxcvbn score, 0 accepts anything
+    pub min_score: u8,
+}
+
+impl GenPassConfig {
+    fn resolve_alphabets(&self) -> GenPassAlphabets {
+        if let Some(alphabets) = &self.alphabets {
+            return alphabets.clone();
+        }
+        let mut alphabets = if self.exclude_ambiguous {
+            GenPassAlphabets::default()
+        } else {
+            GenPassAlphabets::full()
+        };
+        if let Some(symbols) = &self.symbols {
+            alphabets.symbol = symbols.clone();
+        }
+        alphabets
+    }
+}
+
+impl Default for GenPassConfig {
+    fn default() -> Self {
+        GenPassConfig {
+            length: 16,
+            uppercase: true,
+            lowercase: true,
+            number: true,
+            symbol: true,
+            exclude_ambiguous: true,
+            symbols: None,
+            min_uppercase: 1,
+            min_lowercase: 1,
+            min_number: 1,
+            min_symbol: 1,
+            alphabets: None,
+            min_score: 0,
+        }
+    }
+}
+
+impl From<&GenPassOpts> for GenPassConfig {
+    fn from(opts: &GenPassOpts) -> Self {
+        GenPassConfig {
+            length: opts.length,
+            uppercase: opts.uppercase,
+            lowercase: opts.lowercase,
+            number: opts.number,
+            symbol: opts.symbol,
+            exclude_ambiguous: opts.exclude_ambiguous,
+            symbols: opts.symbols.clone(),
+            min_uppercase: opts.min_uppercase,
+            min_lowercase: opts.min_lowercase,
+            min_number: opts.min_number,
+            min_symbol: opts.min_symbol,
+            alphabets: None,
+            min_score: opts.min_score,
+        }
+    }
+}
+
+// a seeded rng makes the output reproducible, otherwise use the thread-local CSPRNG
+pub fn genpass_rng(seed: Option<u64>) -> Box<dyn RngCore> {
+    match seed {
+        Some(seed) => Box::new(StdRng::seed_from_u64(seed)),
+        None => Box::new(rand::thread_rng()),
+    }
+}
+
+pub fn process_genpass(config: &GenPassConfig, rng: &mut impl Rng) -> Result<String, RcliError> {
+    if config.min_score > 4 {
+        return Err(anyhow!("min score must be between 0 and 4").into());
+    }
+    for _ in 0..MAX_SCORE_ATTEMPTS {
+        let password = generate_password(config, rng)?;
+        if config.min_score == 0
+            || zxcvbn(&password, &[]).map_err(anyhow::Error::from)?.score() >= config.min_score
+        {
+            return Ok(password);
+        }
+    }
+    Err(anyhow!(
+        "no password reached score {} in {} attempts, try a longer length",
+        config.min_score,
+        MAX_SCORE_ATTEMPTS
+    )
+    .into())
+}
+
+fn generate_password(config: &GenPassConfig, rng: &mut impl Rng) -> Result<String> {
+    if config.length == 0 {
+        return Err(anyhow!("length must be greater than 0"));
+    }
+
+    let alphabets = config.resolve_alphabets();
+    let classes = [
+        (
+            config.uppercase,
+            alphabets.uppercase.as_bytes(),
+            config.min_uppercase,
+        ),
+        (
+            config.lowercase,
+            alphabets.lowercase.as_bytes(),
+            config.min_lowercase,
+        ),
+        (
+            config.number,
+            alphabets.number.as_bytes(),
+            config.min_number,
+        ),
+        (
+            config.symbol,
+            alphabets.symbol.as_bytes(),
+            config.min_symbol,
+        ),
+    ];
+    // otherwise there'd be nothing to draw the remaining characters from
+    if classes.iter().all(|(enabled, _, _)| !enabled) {
+        return Err(anyhow!(
+            "no character classes enabled, keep at least one of uppercase, lowercase, numbers or symbols"
+        ));
+    }
+    for ((enabled, set, _), (name, plural)) in classes.iter().zip(CLASS_NAMES) {
+        if *enabled && set.is_empty() {
+            return Err(anyhow!(
+                "{} set must not be empty when {} are enabled",
+                name,
+                plural
+            ));
+        }
+        if *enabled && !set.is_ascii() {
+            return Err(anyhow!("{} set must only contain ASCII characters", name));
+        }
+    }
+
+    let required: usize = classes
+        .iter()
+        .filter(|(enabled, _, _)| *enabled)
+        .map(|(_, _, min)| *min as usize)
+        .sum();
+    if (config.length as usize) < required {
+        return Err(anyhow!(
+            "length must be at least {} to include all selected character classes",
+            required
+        ));
     }
-    if symbol == 1 {
-        chars.extend_from_slice(SYMBOLS);
-        password.push(*SYMBOLS.choose(&mut rng).expect("won't be empty"))
+
+    let mut password = Vec::new();
+    let mut chars = Vec::new();
+
+    for (enabled, set, min) in classes {
+        if !enabled {
+            continue;
+        }
+        chars.extend_from_slice(set);
+        for _ in 0..min {
+            password.push(*set.choose(rng).expect("won't be empty"));
+        }
     }
 
-    for _ in 0..(length - password.len() as u8) {
-        let c = chars.choose(&mut rng).expect("won't be empty");
+    for _ in 0..(config.length - password.len() as u8) {
+        let c = chars.choose(rng).expect("won't be empty");
         password.push(*c);
     }
 
-    password.shuffle(&mut rng);
+    password.shuffle(rng);
     let password_str = String::from_utf8(password)?;
 
     Ok(password_str)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_process_genkey() -> Result<()> {
+        for length in [1, 16, 32, 33] {
+            let key = process_genkey(length, GenPassEncoding::Base64, &mut thread_rng())?;
+            assert_eq!(STANDARD.decode(key)?.len(), length);
+
+            let key = process_genkey(length, GenPassEncoding::Hex, &mut thread_rng())?;
+            assert_eq!(hex::decode(key)?.len(), length);
+        }
+        assert!(process_genkey(0, GenPassEncoding::Hex, &mut thread_rng()).is_err());
+        assert!(process_genkey(32, GenPassEncoding::Raw, &mut thread_rng()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_genpass() -> Result<()> {
+        let password = process_genpass(
+            &GenPassConfig {
+                length: 16,
+                ..Default::default()
+            },
+            &mut thread_rng(),
+        )?;
+        assert_eq!(password.len(), 16);
+        assert!(password.bytes().any(|c| UPPERCASE.contains(&c)));
+        assert!(password.bytes().any(|c| LOWERCASE.contains(&c)));
+        assert!(password.bytes().any(|c| NUMBERS.contains(&c)));
+        assert!(password.bytes().any(|c| SYMBOLS.contains(&c)));
+
+        let password = process_genpass(
+            &GenPassConfig {
+                length: 8,
+                uppercase: false,
+                number: false,
+                symbol: false,
+                ..Default::default()
+            },
+            &mut thread_rng(),
+        )?;
+        assert_eq!(password.len(), 8);
+        assert!(password.bytes().all(|c| LOWERCASE.contains(&c)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_genpass_no_classes() {
+        let config = GenPassConfig {
+            uppercase: false,
+            lowercase: false,
+            number: false,
+            symbol: false,
+            ..Default::default()
+        };
+        let err = process_genpass(&config, &mut thread_rng()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "no character classes enabled, keep at least one of uppercase, lowercase, numbers or symbols"
+        );
+    }
+
+    #[test]
+    fn test_process_genpass_length_too_short() {
+        let err = process_genpass(
+            &GenPassConfig {
+                length: 2,
+                ..Default::default()
+            },
+            &mut thread_rng(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "length must be at least 4 to include all selected character classes"
+        );
+        assert!(process_genpass(
+            &GenPassConfig {
+                length: 2,
+                number: false,
+                symbol: false,
+                ..Default::default()
+            },
+            &mut thread_rng()
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_process_genpass_zero_length() {
+        let err = process_genpass(
+            &GenPassConfig {
+                length: 0,
+                ..Default::default()
+            },
+            &mut thread_rng(),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "length must be greater than 0");
+    }
+
+    #[test]
+    fn test_process_genpass_disable_class() -> Result<()> {
+        for _ in 0..100 {
+            let password = process_genpass(
+                &GenPassConfig {
+                    length: 16,
+                    symbol: false,
+                    ..Default::default()
+                },
+                &mut thread_rng(),
+            )?;
+            assert!(!password.bytes().any(|c| SYMBOLS.contains(&c)));
+            let password = process_genpass(
+                &GenPassConfig {
+                    length: 16,
+                    uppercase: false,
+                    ..Default::default()
+                },
+                &mut thread_rng(),
+            )?;
+            assert!(!password.bytes().any(|c| UPPERCASE.contains(&c)));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_genpass_ambiguous() -> Result<()> {
+        let is_ambiguous = |c: u8| b"IOl0".contains(&c);
+        for _ in 0..100 {
+            let password = process_genpass(
+                &GenPassConfig {
+                    length: 64,
+                    ..Default::default()
+                },
+                &mut thread_rng(),
+            )?;
+            assert!(!password.bytes().any(is_ambiguous));
+        }
+
+        let found = (0..10)
+            .map(|_| {
+                process_genpass(
+                    &GenPassConfig {
+                        length: 255,
+                        exclude_ambiguous: false,
+                        ..Default::default()
+                    },
+                    &mut thread_rng(),
+                )
+            })
+            .collect::<Result<Vec<_>, RcliError>>()?
+            .iter()
+            .any(|p| p.bytes().any(is_ambiguous));
+        assert!(found);
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_passphrase() -> Result<()> {
+        let passphrase = process_passphrase(6, "-", &mut thread_rng())?;
+        let words: Vec<_> = passphrase.split('-').collect();
+        assert_eq!(words.len(), 6);
+        assert!(words.iter().all(|w| WORDLIST.lines().any(|l| l == *w)));
+
+        let passphrase = process_passphrase(3, " / ", &mut thread_rng())?;
+        assert_eq!(passphrase.matches(" / ").count(), 2);
+
+        assert!(process_passphrase(0, "-", &mut thread_rng()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_passphrase_entropy() {
+        let opts = GenPassOpts::parse_from(["genpass", "--passphrase"]);
+        let bits = opts.words as f64 * (WORDLIST.lines().count() as f64).log2();
+        assert!(bits >= 64.0, "default passphrase has only {:.1} bits", bits);
+    }
+
+    #[test]
+    fn test_process_password_stats() -> Result<()> {
+        let stats = process_password_stats("password")?;
+        let json: serde_json::Value = serde_json::from_str(&serde_json::to_string(&stats)?)?;
+        assert_eq!(json["score"], 0);
+        assert!(json["guesses"].as_u64().is_some());
+        assert!(json["crack_times"]["offline_fast_hashing_1e10_per_second"].is_string());
+
+        let stats = process_password_stats(&process_genpass(
+            &GenPassConfig {
+                length: 24,
+                ..Default::default()
+            },
+            &mut thread_rng(),
+        )?)?;
+        assert_eq!(stats.score, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_genpass_custom_symbols() -> Result<()> {
+        for _ in 0..100 {
+            let password = process_genpass(
+                &GenPassConfig {
+                    length: 16,
+                    uppercase: false,
+                    lowercase: false,
+                    number: false,
+                    symbols: Some("#@".into()),
+                    ..Default::default()
+                },
+                &mut thread_rng(),
+            )?;
+            assert!(password.bytes().all(|c| c == b'#' || c == b'@'));
+        }
+        let password = process_genpass(
+            &GenPassConfig {
+                length: 16,
+                symbols: Some("-".into()),
+                ..Default::default()
+            },
+            &mut thread_rng(),
+        )?;
+        assert!(password.contains('-'));
+        assert!(!password.bytes().any(|c| SYMBOLS.contains(&c)));
+
+        let err = process_genpass(
+            &GenPassConfig {
+                length: 16,
+                symbols: Some("".into()),
+                ..Default::default()
+            },
+            &mut thread_rng(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "symbol set must not be empty when symbols are enabled"
+        );
+        assert!(process_genpass(
+            &GenPassConfig {
+                length: 16,
+                symbol: false,
+                symbols: Some("".into()),
+                ..Default::default()
+            },
+            &mut thread_rng()
+        )
+        .is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_genpass_custom_alphabets() -> Result<()> {
+        let lowercase = "abcdefghijklmnopqrstuvwxyz".to_string();
+        let config = GenPassConfig {
+            length: 32,
+            alphabets: Some(GenPassAlphabets {
+                uppercase: lowercase.clone(),
+                lowercase: lowercase.clone(),
+                number: lowercase.clone(),
+                symbol: lowercase,
+            }),
+            ..Default::default()
+        };
+        let password = process_genpass(&config, &mut thread_rng())?;
+        assert_eq!(password.len(), 32);
+        assert!(password.bytes().all(|c| c.is_ascii_lowercase()));
+
+        let err = process_genpass(
+            &GenPassConfig {
+                alphabets: Some(GenPassAlphabets {
+                    number: "".into(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            &mut thread_rng(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "number set must not be empty when numbers are enabled"
+        );
+        assert_eq!(
+            GenPassAlphabets::default().symbol,
+            String::from_utf8_lossy(SYMBOLS)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_genpass_min_score() -> Result<()> {
+        let config = GenPassConfig {
+            length: 16,
+            min_score: 3,
+            ..Default::default()
+        };
+        for _ in 0..10 {
+            let password = process_genpass(&config, &mut thread_rng())?;
+            assert!(zxcvbn(&password, &[])?.score() >= 3);
+        }
+
+        // a single character can never score 4
+        let err = process_genpass(
+            &GenPassConfig {
+                length: 1,
+                uppercase: false,
+                number: false,
+                symbol: false,
+                min_score: 4,
+                ..Default::default()
+            },
+            &mut thread_rng(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("100 attempts"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_genpass_min_counts() -> Result<()> {
+        let config = GenPassConfig {
+            length: 12,
+            min_number: 2,
+            min_symbol: 3,
+            ..Default::default()
+        };
+        for _ in 0..200 {
+            let password = process_genpass(&config, &mut thread_rng())?;
+            assert_eq!(password.len(), 12);
+            assert!(password.bytes().filter(|c| NUMBERS.contains(c)).count() >= 2);
+            assert!(password.bytes().filter(|c| SYMBOLS.contains(c)).count() >= 3);
+            assert!(password.bytes().any(|c| UPPERCASE.contains(&c)));
+            assert!(password.bytes().any(|c| LOWERCASE.contains(&c)));
+        }
+
+        let config = GenPassConfig {
+            length: 8,
+            min_number: 4,
+            min_symbol: 4,
+            ..Default::default()
+        };
+        let err = process_genpass(&config, &mut thread_rng()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "length must be at least 10 to include all selected character classes"
+        );
+
+        // minimums of disabled classes don't count
+        let config = GenPassConfig {
+            length: 8,
+            symbol: false,
+            min_symbol: 10,
+            ..Default::default()
+        };
+        assert!(process_genpass(&config, &mut thread_rng()).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_genpass_seed() -> Result<()> {
+        let config = GenPassConfig::default();
+        let mut rng1 = genpass_rng(Some(42));
+        let mut rng2 = genpass_rng(Some(42));
+        let first: Vec<_> = (0..3)
+            .map(|_| process_genpass(&config, &mut rng1))
+            .collect::<Result<_, RcliError>>()?;
+        let second: Vec<_> = (0..3)
+            .map(|_| process_genpass(&config, &mut rng2))
+            .collect::<Result<_, RcliError>>()?;
+        assert_eq!(first, second);
+        // the rng keeps advancing, so a batch doesn't repeat itself
+        assert_ne!(first[0], first[1]);
+
+        let mut rng3 = genpass_rng(Some(7));
+        assert_ne!(process_genpass(&config, &mut rng3)?, first[0]);
+
+        assert_eq!(
+            process_passphrase(4, "-", &mut genpass_rng(Some(42)))?,
+            process_passphrase(4, "-", &mut genpass_rng(Some(42)))?
+        );
+        Ok(())
+    }
+}
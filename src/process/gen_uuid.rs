@@ -0,0 +1,64 @@
+use crate::{RcliError, UuidVersion};
+use uuid::Uuid;
+
+// v7 values from one process are ordered by creation, so a batch is sorted
+pub fn process_uuid(
+    version: UuidVersion,
+    count: usize,
+    simple: bool,
+    uppercase: bool,
+) -> Result<Vec<String>, RcliError> {
+    let uuids = (0..count)
+        .map(|_| {
+            let uuid = match version {
+                UuidVersion::V4 => Uuid::new_v4(),
+                UuidVersion::V7 => Uuid::now_v7(),
+            };
+            let uuid = if simple {
+                uuid.simple().to_string()
+            } else {
+                uuid.hyphenated().to_string()
+            };
+            if uppercase {
+                uuid.to_ascii_uppercase()
+            } else {
+                uuid
+            }
+        })
+        .collect();
+    Ok(uuids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_process_uuid_v4() -> Result<()> {
+        let uuids = process_uuid(UuidVersion::V4, 3, false, false)?;
+        assert_eq!(uuids.len(), 3);
+        for uuid in &uuids {
+            assert_eq!(uuid.len(), 36);
+            assert_eq!(Uuid::parse_str(uuid)?.get_version_num(), 4);
+        }
+        assert_ne!(uuids[0], uuids[1]);
+
+        let uuid = &process_uuid(UuidVersion::V4, 1, true, true)?[0];
+        assert_eq!(uuid.len(), 32);
+        assert!(!uuid.contains('-'));
+        assert_eq!(uuid, &uuid.to_ascii_uppercase());
+        assert_eq!(Uuid::parse_str(uuid)?.get_version_num(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_uuid_v7_ordered() -> Result<()> {
+        let uuids = process_uuid(UuidVersion::V7, 100, false, false)?;
+        for uuid in &uuids {
+            assert_eq!(Uuid::parse_str(uuid)?.get_version_num(), 7);
+        }
+        assert!(uuids.windows(2).all(|pair| pair[0] < pair[1]));
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use super::bench::{bench, BenchReport};
+use crate::{cli::HashOpts, get_buf_bytes, HashAlgo, RcliError};
+use anyhow::Result;
+use sha2::{Digest, Sha256, Sha512};
+
+// how a digest is printed, the default is continuous lowercase hex
+#[derive(Debug, Clone, Default)]
+pub struct DigestFormat {
+    pub uppercase: bool,
+    // hex characters per group, 0 disables grouping
+    pub group: usize,
+    pub separator: String,
+}
+
+impl From<&HashOpts> for DigestFormat {
+    fn from(opts: &HashOpts) -> Self {
+        DigestFormat {
+            uppercase: opts.uppercase,
+            group: opts.group,
+            separator: opts.separator.clone(),
+        }
+    }
+}
+
+pub fn process_hash(
+    input: &str,
+    algo: HashAlgo,
+    format: &DigestFormat,
+) -> Result<String, RcliError> {
+    let buf = get_buf_bytes(input)?;
+    Ok(format_digest(&hash_bytes(&buf, algo), format))
+}
+
+// hashes the loaded input `runs` times, the output is the same as process_hash
+pub fn process_hash_bench(
+    input: &str,
+    algo: HashAlgo,
+    format: &DigestFormat,
+    runs: u32,
+) -> Result<(String, BenchReport), RcliError> {
+    let buf = get_buf_bytes(input)?;
+    let (digest, report) = bench(runs, buf.len(), || Ok(hash_bytes(&buf, algo)))?;
+    Ok((format_digest(&digest, format), report))
+}
+
+// true if the input's digest matches `expected`, hex case is ignored
+pub fn process_hash_check(input: &str, algo: HashAlgo, expected: &str) -> Result<bool, RcliError> {
+    let digest = process_hash(input, algo, &DigestFormat::default())?;
+    Ok(digest.eq_ignore_ascii_case(expected.trim()))
+}
+
+// "deadbeef" with a group of 2 and ":" -> "de:ad:be:ef"
+fn format_digest(digest: &str, format: &DigestFormat) -> String {
+    let digest = if format.uppercase {
+        digest.to_ascii_uppercase()
+    } else {
+        digest.to_string()
+    };
+    if format.group == 0 {
+        return digest;
+    }
+    digest
+        .as_bytes()
+        .chunks(format.group)
+        .map(|chunk| String::from_utf8_lossy(chunk))
+        .collect::<Vec<_>>()
+        .join(&format.separator)
+}
+
+fn hash_bytes(data: &[u8], algo: HashAlgo) -> String {
+    let digest = match algo {
+        HashAlgo::Sha256 => Sha256::digest(data).to_vec(),
+        HashAlgo::Sha512 => Sha512::digest(data).to_vec(),
+        HashAlgo::Blake3 => blake3::hash(data).as_bytes().to_vec(),
+    };
+    hex::encode(digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            hash_bytes(b"abc", HashAlgo::Sha256),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hash_bytes(b"", HashAlgo::Sha256),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_sha512() {
+        assert_eq!(
+            hash_bytes(b"abc", HashAlgo::Sha512),
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+             2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        );
+    }
+
+    #[test]
+    fn test_blake3() {
+        assert_eq!(
+            hash_bytes(b"", HashAlgo::Blake3),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+        assert_eq!(
+            hash_bytes(b"abc", HashAlgo::Blake3),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+    }
+
+    #[test]
+    fn test_format_digest() {
+        let digest = hash_bytes(b"abc", HashAlgo::Sha256);
+        assert_eq!(format_digest(&digest, &DigestFormat::default()), digest);
+
+        let grouped = DigestFormat {
+            group: 2,
+            separator: ":".into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_digest(&digest, &grouped),
+            "ba:78:16:bf:8f:01:cf:ea:41:41:40:de:5d:ae:22:23:\
+             b0:03:61:a3:96:17:7a:9c:b4:10:ff:61:f2:00:15:ad"
+        );
+
+        let upper = DigestFormat {
+            uppercase: true,
+            group: 8,
+            separator: " ".into(),
+        };
+        assert_eq!(format_digest("deadbeefcafe", &upper), "DEADBEEF CAFE");
+    }
+
+    #[test]
+    fn test_process_hash_check() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("abc.txt");
+        fs::write(&path, "abc")?;
+        let path = path.to_str().unwrap();
+
+        let expected = "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD";
+        assert!(process_hash_check(path, HashAlgo::Sha256, expected)?);
+        assert!(!process_hash_check(path, HashAlgo::Blake3, expected)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use crate::{get_buf_bytes, RcliError};
+use anyhow::Result;
+
+pub fn process_hex_encode(input: &str, upper: bool) -> Result<String, RcliError> {
+    let buf = get_buf_bytes(input)?;
+    Ok(hex_encode(&buf, upper))
+}
+
+pub fn process_hex_decode(input: &str) -> Result<Vec<u8>, RcliError> {
+    let buf = get_buf_bytes(input)?;
+    Ok(hex_decode(&String::from_utf8(buf)?)?)
+}
+
+fn hex_encode(buf: &[u8], upper: bool) -> String {
+    if upper {
+        hex::encode_upper(buf)
+    } else {
+        hex::encode(buf)
+    }
+}
+
+// "0xDEAD BEEF\n" and "deadbeef" decode to the same bytes
+fn hex_decode(input: &str) -> Result<Vec<u8>> {
+    let digits: String = input
+        .split_ascii_whitespace()
+        .map(|s| {
+            s.strip_prefix("0x")
+                .or_else(|| s.strip_prefix("0X"))
+                .unwrap_or(s)
+        })
+        .collect();
+    Ok(hex::decode(digits)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_hex_encode() {
+        assert_eq!(hex_encode(&[0xde, 0xad, 0xbe, 0xef], false), "deadbeef");
+        assert_eq!(hex_encode(&[0xde, 0xad, 0xbe, 0xef], true), "DEADBEEF");
+        assert_eq!(hex_encode(&[], false), "");
+    }
+
+    #[test]
+    fn test_hex_decode() -> Result<()> {
+        assert_eq!(hex_decode("0xDEADBEEF")?, [0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(hex_decode("de ad\nbe ef\n")?, [0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(hex_decode("0xdead 0Xbeef")?, [0xde, 0xad, 0xbe, 0xef]);
+        assert!(hex_decode("abc").is_err());
+        assert!(hex_decode("zz").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_hex_round_trip() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let original = fs::read("fixtures/ed25519.sk")?;
+        for upper in [false, true] {
+            let path = dir.path().join("key.hex");
+            fs::write(&path, process_hex_encode("fixtures/ed25519.sk", upper)?)?;
+            assert_eq!(process_hex_decode(path.to_str().unwrap())?, original);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{check_overwrite, get_reader, get_writer, RcliError};
+use anyhow::{anyhow, Result};
+use serde_json::{Map, Value};
+use std::io::{BufRead, BufReader, Write};
+
+pub fn process_json2csv(input: &str, output: &str, force: bool) -> Result<(), RcliError> {
+    check_overwrite(output, force)?;
+    let reader = BufReader::new(get_reader(input)?);
+    let writer = get_writer(output)?;
+    Ok(json_to_csv(reader, writer)?)
+}
+
+// headers are the union of all keys in first-seen order, so every record is
+// read before the header row can be written
+fn json_to_csv(reader: impl BufRead, writer: impl Write) -> Result<()> {
+    let mut headers: Vec<String> = Vec::new();
+    let mut records = Vec::with_capacity(128);
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: Map<String, Value> = serde_json::from_str(&line)
+            .map_err(|e| anyhow!("line {}: expected a JSON object: {}", i + 1, e))?;
+        for key in record.keys() {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+        records.push(record);
+    }
+
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record(&headers)?;
+    for record in &records {
+        writer.write_record(headers.iter().map(|h| cell(record.get(h))))?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+// missing keys and nulls become empty cells, nested values stay as JSON
+fn cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(v) => v.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_json_to_csv_ragged() -> Result<()> {
+        let input = r#"{"name":"Perin","kit":37}
+{"name":"Buffon","nationality":"Italy"}
+
+{"kit":1,"tags":["gk"],"name":"Szczesny","captain":null}
+"#;
+        let mut buf = Vec::new();
+        json_to_csv(input.as_bytes(), &mut buf)?;
+
+        assert_eq!(
+            String::from_utf8(buf)?,
+            "name,kit,nationality,tags,captain\n\
+             Perin,37,,,\n\
+             Buffon,,Italy,,\n\
+             Szczesny,1,,\"[\"\"gk\"\"]\",\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_json2csv_needs_force() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let input = dir.path().join("input.json");
+        fs::write(&input, "{\"name\":\"Perin\"}\n")?;
+        let output = dir.path().join("output.csv");
+        fs::write(&output, "keep")?;
+        let (input, output_str) = (input.to_str().unwrap(), output.to_str().unwrap());
+
+        assert!(process_json2csv(input, output_str, false).is_err());
+        assert_eq!(fs::read_to_string(&output)?, "keep");
+        process_json2csv(input, output_str, true)?;
+        assert_eq!(fs::read_to_string(&output)?, "name\nPerin\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_to_csv_invalid_line() {
+        let input = "{\"name\":\"Perin\"}\n[1,2]\n";
+        let err = json_to_csv(input.as_bytes(), Vec::new()).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("line 2: expected a JSON object"));
+    }
+}
@@ -0,0 +1,214 @@
+use super::text::{Ed25519Signer, Ed25519Verifier, HmacSha256, KeyLoader, TextSign, TextVerify};
+use crate::{JwtAlg, RcliError};
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JwtHeader {
+    alg: String,
+    typ: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JwtClaims {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    pub iat: u64,
+    pub exp: u64,
+}
+
+pub fn process_jwt_sign(
+    key: &str,
+    alg: JwtAlg,
+    sub: Option<String>,
+    aud: Option<String>,
+    ttl: u64,
+) -> Result<String, RcliError> {
+    let iat = now()?;
+    let claims = JwtClaims {
+        sub,
+        aud,
+        iat,
+        exp: iat.saturating_add(ttl),
+    };
+    Ok(sign_token(key, alg, &claims)?)
+}
+
+pub fn process_jwt_verify(
+    token: &str,
+    key: &str,
+    alg: JwtAlg,
+    aud: Option<&str>,
+) -> Result<JwtClaims, RcliError> {
+    Ok(verify_token(token, key, alg, aud, now()?)?)
+}
+
+fn sign_token(key: &str, alg: JwtAlg, claims: &JwtClaims) -> Result<String> {
+    let header = JwtHeader {
+        alg: alg.to_string(),
+        typ: "JWT".into(),
+    };
+    let header = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&header)?);
+    let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(claims)?);
+    let signing_input = format!("{}.{}", header, payload);
+
+    let sig = match alg {
+        JwtAlg::Hs256 => HmacSha256::load(key)?.sign(signing_input.as_bytes())?,
+        JwtAlg::EdDsa => Ed25519Signer::load(key)?.sign(signing_input.as_bytes())?,
+    };
+    Ok(format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(sig)))
+}
+
+fn verify_token(
+    token: &str,
+    key: &str,
+    alg: JwtAlg,
+    aud: Option<&str>,
+    now: u64,
+) -> Result<JwtClaims> {
+    let parts: Vec<&str> = token.trim().split('.').collect();
+    let [header, payload, sig] = parts[..] else {
+        return Err(anyhow!("malformed token: expected 3 dot-separated parts"));
+    };
+
+    let decoded: JwtHeader = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(header)?)?;
+    if decoded.alg != alg.to_string() {
+        return Err(anyhow!(
+            "token is signed with {}, expected {}",
+            decoded.alg,
+            alg
+        ));
+    }
+
+    let signing_input = format!("{}.{}", header, payload);
+    let sig = URL_SAFE_NO_PAD.decode(sig)?;
+    let verified = match alg {
+        JwtAlg::Hs256 => HmacSha256::load(key)?.verify(signing_input.as_bytes(), &sig)?,
+        JwtAlg::EdDsa => Ed25519Verifier::load(key)?.verify(signing_input.as_bytes(), &sig)?,
+    };
+    if !verified {
+        return Err(RcliError::SignatureInvalid.into());
+    }
+
+    let claims: JwtClaims = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload)?)?;
+    if claims.exp <= now {
+        return Err(anyhow!("token expired at {}", claims.exp));
+    }
+    if let Some(aud) = aud {
+        if claims.aud.as_deref() != Some(aud) {
+            return Err(anyhow!("token audience does not match {}", aud));
+        }
+    }
+    Ok(claims)
+}
+
+fn now() -> Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(exp: u64) -> JwtClaims {
+        JwtClaims {
+            sub: Some("alice".into()),
+            aud: Some("rcli".into()),
+            iat: 1_000,
+            exp,
+        }
+    }
+
+    #[test]
+    fn test_jwt_hs256_valid() -> Result<()> {
+        let token = sign_token("fixtures/hmac-sha256.key", JwtAlg::Hs256, &claims(2_000))?;
+        let verified = verify_token(
+            &token,
+            "fixtures/hmac-sha256.key",
+            JwtAlg::Hs256,
+            Some("rcli"),
+            1_500,
+        )?;
+        assert_eq!(verified, claims(2_000));
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_eddsa_valid() -> Result<()> {
+        let token = sign_token("fixtures/ed25519.sk", JwtAlg::EdDsa, &claims(2_000))?;
+        let verified = verify_token(&token, "fixtures/ed25519.pk", JwtAlg::EdDsa, None, 1_500)?;
+        assert_eq!(verified.sub.as_deref(), Some("alice"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_expired() -> Result<()> {
+        let token = sign_token("fixtures/hmac-sha256.key", JwtAlg::Hs256, &claims(2_000))?;
+        let err = verify_token(
+            &token,
+            "fixtures/hmac-sha256.key",
+            JwtAlg::Hs256,
+            None,
+            2_000,
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "token expired at 2000");
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_tampered_payload() -> Result<()> {
+        let token = sign_token("fixtures/hmac-sha256.key", JwtAlg::Hs256, &claims(2_000))?;
+        let forged = sign_token("fixtures/blake3.key", JwtAlg::Hs256, &claims(9_000))?;
+
+        // splice the forged payload into the genuine token
+        let parts: Vec<&str> = token.split('.').collect();
+        let forged_payload = forged.split('.').nth(1).unwrap();
+        let tampered = format!("{}.{}.{}", parts[0], forged_payload, parts[2]);
+
+        let err = verify_token(
+            &tampered,
+            "fixtures/hmac-sha256.key",
+            JwtAlg::Hs256,
+            None,
+            1_500,
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "invalid token signature");
+        assert!(matches!(RcliError::from(err), RcliError::SignatureInvalid));
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_public_key_as_hmac_secret() -> Result<()> {
+        // anyone holding the public key can MAC an HS256 token with it
+        let forged = sign_token("fixtures/ed25519.pk", JwtAlg::Hs256, &claims(2_000))?;
+        let err =
+            verify_token(&forged, "fixtures/ed25519.pk", JwtAlg::EdDsa, None, 1_500).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "token is signed with HS256, expected EdDSA"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_wrong_alg_or_audience() -> Result<()> {
+        let token = sign_token("fixtures/hmac-sha256.key", JwtAlg::Hs256, &claims(2_000))?;
+        assert!(verify_token(&token, "fixtures/ed25519.pk", JwtAlg::EdDsa, None, 1_500).is_err());
+        let err = verify_token(
+            &token,
+            "fixtures/hmac-sha256.key",
+            JwtAlg::Hs256,
+            Some("other"),
+            1_500,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("audience"));
+        Ok(())
+    }
+}
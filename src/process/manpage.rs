@@ -0,0 +1,41 @@
+use crate::{Opts, RcliError};
+use anyhow::Result;
+use clap::{Command, CommandFactory};
+use clap_mangen::Man;
+use std::path::{Path, PathBuf};
+
+// writes rcli.1 plus rcli-<sub>.1 for every subcommand, returning the files written
+pub fn process_manpage(out_dir: impl AsRef<Path>) -> Result<Vec<PathBuf>, RcliError> {
+    let mut cmd = Opts::command().disable_help_subcommand(true);
+    // fills in the `rcli-sub` names used for the subcommand pages
+    cmd.build();
+    let mut paths = Vec::new();
+    write_manpages(cmd, out_dir.as_ref(), &mut paths)?;
+    Ok(paths)
+}
+
+fn write_manpages(cmd: Command, out_dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    for sub in cmd.get_subcommands().filter(|s| !s.is_hide_set()) {
+        write_manpages(sub.clone(), out_dir, paths)?;
+    }
+    paths.push(Man::new(cmd).generate_to(out_dir)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_process_manpage() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let paths = process_manpage(dir.path())?;
+        let page = fs::read_to_string(dir.path().join("rcli.1"))?;
+        // the top-level page links every subcommand page
+        assert!(page.contains("rcli\\-genpass(1)"));
+        assert!(paths.contains(&dir.path().join("rcli-genpass.1")));
+        assert!(paths.contains(&dir.path().join("rcli-base64-encode.1")));
+        Ok(())
+    }
+}
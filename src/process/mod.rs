@@ -1,9 +1,49 @@
 mod b64;
+mod base32;
+mod bench;
+mod completion;
+mod compress;
 mod csv_convert;
+mod detect;
 mod gen_pass;
+mod gen_uuid;
+mod hash;
+mod hex;
+mod json2csv;
+mod jwt;
+mod manpage;
+mod otp;
 mod text;
 
-pub use b64::{process_decode, process_encode};
-pub use csv_convert::process_csv;
-pub use gen_pass::process_genpass;
-pub use text::{process_keygen, process_sign, process_verify};
+pub use b64::{
+    process_decode, process_decode_bench, process_decode_literal, process_decode_stream,
+    process_encode, process_encode_bench, process_encode_files, process_encode_literal,
+    process_encode_stream,
+};
+pub use base32::{process_base32_decode, process_base32_encode};
+pub use bench::BenchReport;
+pub use completion::process_completion;
+pub use compress::{process_compress, process_decompress};
+pub use csv_convert::{
+    csv_to_json, process_csv, process_csv_split, process_csv_stats, process_csv_validate, CsvStats,
+    CsvSummary,
+};
+pub use detect::{process_detect, DataType};
+pub use gen_pass::{
+    genpass_rng, process_genkey, process_genpass, process_passphrase, process_password_stats,
+    GenPassAlphabets, GenPassConfig,
+};
+pub use gen_uuid::process_uuid;
+pub use hash::{process_hash, process_hash_bench, process_hash_check, DigestFormat};
+pub use hex::{process_hex_decode, process_hex_encode};
+pub use json2csv::process_json2csv;
+pub use jwt::{process_jwt_sign, process_jwt_verify, JwtClaims};
+pub use manpage::process_manpage;
+pub use otp::{process_otp_generate, process_otp_verify, read_otp_secret, Totp};
+pub use text::{
+    derive_key, key_fingerprint, key_paths, process_decrypt, process_encrypt, process_keygen,
+    process_keygen_to_dir, process_recover_pubkey, process_sign, process_sign_files,
+    process_sign_lines, process_sign_manifest, process_sign_passphrase, process_verify,
+    process_verify_files, process_verify_lines, process_verify_manifest, process_verify_passphrase,
+    read_salt, read_signature, signature_format, VerifyOutcome, VerifyReport,
+};
@@ -0,0 +1,154 @@
+use super::base32::base32_decode;
+use crate::{get_buf, OtpAlgo, RcliError};
+use anyhow::{anyhow, Result};
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use sha2::Sha256;
+use std::time::{SystemTime, UNIX_EPOCH};
+use subtle::ConstantTimeEq;
+
+// a code from the previous or next period is still accepted
+const DRIFT_WINDOW: i64 = 1;
+
+pub struct Totp {
+    secret: Vec<u8>,
+    digits: u32,
+    period: u64,
+    algo: OtpAlgo,
+}
+
+impl Totp {
+    pub fn new(secret: Vec<u8>, digits: u32, period: u64, algo: OtpAlgo) -> Self {
+        Totp {
+            secret,
+            digits,
+            period,
+            algo,
+        }
+    }
+
+    // spaces, lowercase and "=" padding are tolerated, "JBSW Y3DP ehpk 3pxp" is fine
+    pub fn from_base32(secret: &str, digits: u32, period: u64, algo: OtpAlgo) -> Result<Self> {
+        let secret =
+            base32_decode(secret).map_err(|e| anyhow!("secret is not valid base32: {}", e))?;
+        Ok(Self::new(secret, digits, period, algo))
+    }
+
+    pub fn generate(&self, time: u64) -> Result<String> {
+        self.hotp(time / self.period)
+    }
+
+    pub fn verify(&self, code: &str, time: u64) -> Result<bool> {
+        let counter = (time / self.period) as i64;
+        for offset in -DRIFT_WINDOW..=DRIFT_WINDOW {
+            let Ok(counter) = u64::try_from(counter + offset) else {
+                continue;
+            };
+            let expected = self.hotp(counter)?;
+            if bool::from(expected.as_bytes().ct_eq(code.trim().as_bytes())) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    // RFC 4226 HOTP with dynamic truncation
+    fn hotp(&self, counter: u64) -> Result<String> {
+        let msg = counter.to_be_bytes();
+        let digest = match self.algo {
+            OtpAlgo::Sha1 => mac::<Hmac<Sha1>>(&self.secret, &msg)?,
+            OtpAlgo::Sha256 => mac::<Hmac<Sha256>>(&self.secret, &msg)?,
+        };
+        let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+        let bytes: [u8; 4] = digest[offset..offset + 4].try_into()?;
+        let code = (u32::from_be_bytes(bytes) & 0x7fff_ffff) % 10u32.pow(self.digits);
+        Ok(format!("{:0width$}", code, width = self.digits as usize))
+    }
+}
+
+fn mac<M: Mac + hmac::digest::KeyInit>(key: &[u8], msg: &[u8]) -> Result<Vec<u8>> {
+    let mut mac = <M as Mac>::new_from_slice(key)?;
+    mac.update(msg);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+pub fn process_otp_generate(
+    secret: &str,
+    digits: u32,
+    period: u64,
+    algo: OtpAlgo,
+) -> Result<String, RcliError> {
+    Ok(Totp::from_base32(secret, digits, period, algo)?.generate(now()?)?)
+}
+
+pub fn process_otp_verify(
+    secret: &str,
+    code: &str,
+    digits: u32,
+    period: u64,
+    algo: OtpAlgo,
+) -> Result<bool, RcliError> {
+    Ok(Totp::from_base32(secret, digits, period, algo)?.verify(code, now()?)?)
+}
+
+// the secret file may end with a newline, get_buf trims it
+pub fn read_otp_secret(path: &str) -> Result<String, RcliError> {
+    Ok(get_buf(path)?)
+}
+
+fn now() -> Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 6238 appendix B
+    const VECTORS: [(u64, &str, &str); 6] = [
+        (59, "94287082", "46119246"),
+        (1111111109, "07081804", "68084774"),
+        (1111111111, "14050471", "67062674"),
+        (1234567890, "89005924", "91819424"),
+        (2000000000, "69279037", "90698825"),
+        (20000000000, "65353130", "77737706"),
+    ];
+
+    #[test]
+    fn test_totp_rfc6238_vectors() -> Result<()> {
+        let sha1 = Totp::new(b"12345678901234567890".to_vec(), 8, 30, OtpAlgo::Sha1);
+        let sha256 = Totp::new(
+            b"12345678901234567890123456789012".to_vec(),
+            8,
+            30,
+            OtpAlgo::Sha256,
+        );
+        for (time, sha1_code, sha256_code) in VECTORS {
+            assert_eq!(sha1.generate(time)?, sha1_code, "sha1 at {}", time);
+            assert_eq!(sha256.generate(time)?, sha256_code, "sha256 at {}", time);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_totp_from_base32() -> Result<()> {
+        // base32 of "12345678901234567890"
+        let secret = "gezd gnbv gy3t qojq gezd gnbv gy3t qojq";
+        let totp = Totp::from_base32(secret, 6, 30, OtpAlgo::Sha1)?;
+        assert_eq!(totp.generate(59)?, "287082");
+        assert!(Totp::from_base32("not base32!", 6, 30, OtpAlgo::Sha1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_totp_verify_window() -> Result<()> {
+        let totp = Totp::new(b"12345678901234567890".to_vec(), 8, 30, OtpAlgo::Sha1);
+        // 07081804 is valid for 1111111080..1111111110
+        assert!(totp.verify("07081804", 1111111109)?);
+        assert!(totp.verify("07081804", 1111111130)?);
+        assert!(totp.verify("07081804", 1111111060)?);
+        assert!(!totp.verify("07081804", 1111111150)?);
+        assert!(!totp.verify("00000000", 1111111109)?);
+        Ok(())
+    }
+}
@@ -1,15 +1,187 @@
 use anyhow::Result;
-use std::{fs::File, io::Read};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufWriter, Cursor, IsTerminal, Read, Write},
+    path::Path,
+};
 
-pub fn get_buf(input: &str) -> Result<String> {
-    let mut reader: Box<dyn Read> = if input == "-" {
+pub fn get_reader(input: &str) -> Result<Box<dyn Read>> {
+    let reader: Box<dyn Read> = if input == "-" {
         Box::new(std::io::stdin())
     } else {
         Box::new(File::open(input)?)
     };
 
+    Ok(reader)
+}
+
+// reads the inputs back to back, in order, with `separator` between each pair
+pub fn get_concat_reader(inputs: &[String], separator: &[u8]) -> Result<Box<dyn Read>> {
+    let mut reader: Box<dyn Read> = Box::new(std::io::empty());
+    for (i, input) in inputs.iter().enumerate() {
+        if i > 0 {
+            reader = Box::new(reader.chain(Cursor::new(separator.to_vec())));
+        }
+        reader = Box::new(reader.chain(get_reader(input)?));
+    }
+
+    Ok(reader)
+}
+
+pub fn is_url(input: &str) -> bool {
+    input.starts_with("http://") || input.starts_with("https://")
+}
+
+pub fn get_buf(input: &str) -> Result<String> {
+    let mut reader = get_reader(input)?;
     let mut buf = String::new();
     reader.read_to_string(&mut buf)?;
 
     Ok(buf.trim().to_owned())
 }
+
+// unlike get_buf the content is returned byte-exact, signatures and encodings
+// must not silently drop a trailing newline
+pub fn get_buf_bytes(input: &str) -> Result<Vec<u8>> {
+    let mut reader = get_reader(input)?;
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf)?;
+
+    Ok(buf)
+}
+
+// refuses to replace an existing file unless `force` is set, "-" is stdout and always allowed
+pub fn check_overwrite(output: impl AsRef<Path>, force: bool) -> Result<()> {
+    let output = output.as_ref();
+    if !force && output != Path::new("-") && output.exists() {
+        anyhow::bail!(
+            "{} already exists, use --force to overwrite",
+            output.display()
+        );
+    }
+    Ok(())
+}
+
+// like check_overwrite, but asks on the terminal instead of failing when stdin and stderr are
+// both a TTY. Non-interactive runs still need `force`.
+pub fn confirm_overwrite(output: impl AsRef<Path>, force: bool) -> Result<()> {
+    let (stdin, stderr) = (io::stdin(), io::stderr());
+    if !stdin.is_terminal() || !stderr.is_terminal() {
+        return check_overwrite(output, force);
+    }
+    prompt_overwrite(output, force, &mut stdin.lock(), &mut stderr.lock())
+}
+
+fn prompt_overwrite(
+    output: impl AsRef<Path>,
+    force: bool,
+    answer: &mut impl BufRead,
+    prompt: &mut impl Write,
+) -> Result<()> {
+    let output = output.as_ref();
+    if check_overwrite(output, force).is_ok() {
+        return Ok(());
+    }
+    write!(prompt, "Overwrite {}? [y/N] ", output.display())?;
+    prompt.flush()?;
+    let mut line = String::new();
+    answer.read_line(&mut line)?;
+    match line.trim() {
+        "y" | "Y" | "yes" => Ok(()),
+        _ => anyhow::bail!("{} not overwritten", output.display()),
+    }
+}
+
+// "-" writes to stdout
+pub fn write_output(output: impl AsRef<Path>, data: impl AsRef<[u8]>, force: bool) -> Result<()> {
+    if output.as_ref() == Path::new("-") {
+        let mut stdout = std::io::stdout();
+        stdout.write_all(data.as_ref())?;
+        return Ok(stdout.flush()?);
+    }
+    check_overwrite(&output, force)?;
+    fs::write(output, data)?;
+    Ok(())
+}
+
+pub fn get_writer(output: &str) -> Result<Box<dyn Write>> {
+    let writer: Box<dyn Write> = if output == "-" {
+        Box::new(std::io::stdout())
+    } else {
+        Box::new(File::create(output)?)
+    };
+
+    Ok(Box::new(BufWriter::new(writer)))
+}
+
+// like get_writer, but adds to the end of an existing file instead of truncating it
+pub fn get_append_writer(output: &str) -> Result<Box<dyn Write>> {
+    if output == "-" {
+        return get_writer(output);
+    }
+    let file = OpenOptions::new().create(true).append(true).open(output)?;
+
+    Ok(Box::new(BufWriter::new(file)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_concat_reader() -> Result<()> {
+        let inputs = ["fixtures/b64.txt".to_string(), "Cargo.toml".to_string()];
+        let mut buf = Vec::new();
+        get_concat_reader(&inputs, b"--")?.read_to_end(&mut buf)?;
+
+        let mut expected = std::fs::read("fixtures/b64.txt")?;
+        expected.extend(b"--");
+        expected.extend(std::fs::read("Cargo.toml")?);
+        assert_eq!(buf, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_output_no_clobber() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("out.txt");
+        write_output(&path, "first", false)?;
+        assert_eq!(fs::read_to_string(&path)?, "first");
+
+        let err = write_output(&path, "second", false).unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+        assert_eq!(fs::read_to_string(&path)?, "first");
+
+        write_output(&path, "second", true)?;
+        assert_eq!(fs::read_to_string(&path)?, "second");
+        Ok(())
+    }
+
+    #[test]
+    fn test_prompt_overwrite() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("ed25519.sk");
+        let mut prompt = Vec::new();
+        // nothing to overwrite, so nothing is asked
+        prompt_overwrite(&path, false, &mut "".as_bytes(), &mut prompt)?;
+        assert!(prompt.is_empty());
+
+        fs::write(&path, "old")?;
+        let err = prompt_overwrite(&path, false, &mut "n\n".as_bytes(), &mut prompt).unwrap_err();
+        assert!(err.to_string().contains("not overwritten"));
+        assert_eq!(
+            String::from_utf8(prompt)?,
+            format!("Overwrite {}? [y/N] ", path.display())
+        );
+        // an empty answer is the default, no
+        assert!(prompt_overwrite(&path, false, &mut "\n".as_bytes(), &mut Vec::new()).is_err());
+        prompt_overwrite(&path, false, &mut "y\n".as_bytes(), &mut Vec::new())?;
+        assert_eq!(fs::read_to_string(&path)?, "old");
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_overwrite_stdout() -> Result<()> {
+        check_overwrite("-", false)
+    }
+}
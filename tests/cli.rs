@@ -0,0 +1,396 @@
+use std::process::Command;
+
+fn rcli(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_rcli"))
+        .args(args)
+        .output()
+        .expect("failed to run rcli")
+}
+
+#[test]
+fn csv_prints_summary_on_stderr() {
+    let output = rcli(&["csv", "-i", "fixtures/players.csv", "-o", "-"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "Converted 2 rows, 5 columns\n"
+    );
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("Converted"));
+
+    let output = rcli(&["csv", "-i", "fixtures/players.csv", "-o", "-", "--quiet"]);
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn genpass_count_prints_one_password_per_line() {
+    let output = rcli(&["genpass", "--count", "5", "-l", "12"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert!(lines.iter().all(|l| l.len() == 12));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.matches("Password strength").count(), 5);
+}
+
+#[test]
+fn base64_decode_binary_to_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("binary.bin");
+    let ret = rcli(&[
+        "base64",
+        "decode",
+        "-i",
+        "fixtures/binary.b64",
+        "-o",
+        output.to_str().unwrap(),
+    ]);
+    assert!(ret.status.success());
+    assert!(ret.stdout.is_empty());
+    assert_eq!(
+        std::fs::read(output).unwrap(),
+        [0x00, 0x9f, 0x92, 0x96, 0xff, 0xfe, 0x01, 0x80, 0x7f]
+    );
+}
+
+#[test]
+fn outputs_need_force_to_overwrite() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("input.hex");
+    std::fs::write(&input, "6869").unwrap();
+    let base32_input = dir.path().join("input.b32");
+    std::fs::write(&base32_input, "NBUQ====").unwrap();
+    let output = dir.path().join("existing");
+    std::fs::write(&output, "keep").unwrap();
+    let (input, output) = (input.to_str().unwrap(), output.to_str().unwrap());
+
+    let hex = ["hex", "decode", "-i", input, "-o", output];
+    let base32_input = base32_input.to_str().unwrap();
+    let base32 = ["base32", "decode", "-i", base32_input, "-o", output];
+    let sign = [
+        "text",
+        "sign",
+        "-i",
+        input,
+        "-k",
+        "fixtures/blake3.key",
+        "-o",
+        output,
+    ];
+    let sign_lines = [&sign[..], &["--lines"]].concat();
+    for args in [&hex[..], &base32[..], &sign[..], &sign_lines[..]] {
+        let ret = rcli(args);
+        assert!(!ret.status.success());
+        assert!(String::from_utf8_lossy(&ret.stderr).contains("already exists"));
+        assert_eq!(std::fs::read_to_string(output).unwrap(), "keep");
+        assert!(rcli(&[args, &["--force"]].concat()).status.success());
+        std::fs::write(output, "keep").unwrap();
+    }
+}
+
+#[test]
+fn base64_decode_binary_to_stdout() {
+    let ret = rcli(&["base64", "decode", "-i", "fixtures/binary.b64"]);
+    assert!(ret.status.success());
+    assert_eq!(
+        ret.stdout,
+        [0x00, 0x9f, 0x92, 0x96, 0xff, 0xfe, 0x01, 0x80, 0x7f]
+    );
+
+    let ret = rcli(&["base64", "decode", "-i", "fixtures/binary.b64", "--text"]);
+    assert!(!ret.status.success());
+}
+
+#[test]
+fn base64_literal_input() {
+    let ret = rcli(&["base64", "decode", "--literal", "SGVsbG8", "--text"]);
+    assert!(ret.status.success());
+    assert_eq!(String::from_utf8_lossy(&ret.stdout), "Hello\n");
+
+    let ret = rcli(&["base64", "encode", "--literal", "Hello"]);
+    assert!(ret.status.success());
+    assert_eq!(String::from_utf8_lossy(&ret.stdout), "SGVsbG8=\n");
+
+    // without --literal the value is still a path
+    let ret = rcli(&["base64", "decode", "-i", "SGVsbG8"]);
+    assert!(!ret.status.success());
+    let ret = rcli(&["base64", "decode", "--literal", "SGVsbG8", "-i", "-"]);
+    assert!(!ret.status.success());
+}
+
+#[test]
+fn csv_infers_format_from_output_extension() {
+    let dir = tempfile::tempdir().unwrap();
+    let yaml = dir.path().join("players.yaml");
+    let output = rcli(&[
+        "csv",
+        "-i",
+        "fixtures/players.csv",
+        "-o",
+        yaml.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    assert!(std::fs::read_to_string(&yaml)
+        .unwrap()
+        .starts_with("- Name: "));
+
+    let unknown = dir.path().join("players.txt");
+    let output = rcli(&[
+        "csv",
+        "-i",
+        "fixtures/players.csv",
+        "-o",
+        unknown.to_str().unwrap(),
+    ]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("pass --format"));
+    assert!(!unknown.exists());
+}
+
+#[test]
+fn bench_keeps_normal_output() {
+    for args in [
+        &["base64", "encode", "-i", "fixtures/binary.bin"][..],
+        &["base64", "decode", "-i", "fixtures/binary.b64"],
+        &["hash", "-i", "fixtures/binary.bin", "--algo", "blake3"],
+    ] {
+        let plain = rcli(args);
+        let bench = rcli(&[args, &["--bench", "2"]].concat());
+        assert!(bench.status.success());
+        assert_eq!(bench.stdout, plain.stdout);
+        let stderr = String::from_utf8_lossy(&bench.stderr);
+        assert!(stderr.starts_with("2 runs over "));
+        assert!(stderr.contains("ops/sec") && stderr.contains("MB/s"));
+    }
+}
+
+#[test]
+fn compress_round_trip_through_files() {
+    let dir = tempfile::tempdir().unwrap();
+    for algo in ["zstd", "gzip"] {
+        let compressed = dir.path().join(format!("binary.{}", algo));
+        let compressed = compressed.to_str().unwrap();
+        let ret = rcli(&[
+            "compress",
+            "-i",
+            "fixtures/binary.bin",
+            "-o",
+            compressed,
+            "--algo",
+            algo,
+            "--level",
+            "3",
+        ]);
+        assert!(ret.status.success());
+
+        let ret = rcli(&["decompress", "-i", compressed, "--algo", algo]);
+        assert!(ret.status.success());
+        assert_eq!(ret.stdout, std::fs::read("fixtures/binary.bin").unwrap());
+    }
+}
+
+#[test]
+fn text_generate_dry_run_writes_nothing() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().to_str().unwrap();
+    let ret = rcli(&[
+        "text",
+        "generate",
+        "--format",
+        "ed25519",
+        "-o",
+        output,
+        "--dry-run",
+    ]);
+    assert!(ret.status.success());
+    let stderr = String::from_utf8_lossy(&ret.stderr);
+    assert!(stderr.contains("ed25519.sk"));
+    assert!(stderr.contains("ed25519.pk"));
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn text_generate_mkdir_creates_output_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let nested = dir.path().join("keys/blake3");
+    let output = nested.to_str().unwrap();
+    let ret = rcli(&["text", "generate", "-o", output]);
+    assert!(!ret.status.success());
+    assert!(!nested.exists());
+
+    let ret = rcli(&["text", "generate", "-o", output, "--mkdir"]);
+    assert!(ret.status.success());
+    assert!(nested.join("blake3.key").exists());
+}
+
+#[test]
+fn completion_prints_bash_script() {
+    let ret = rcli(&["completion", "bash"]);
+    assert!(ret.status.success());
+    let script = String::from_utf8_lossy(&ret.stdout);
+    assert!(!script.is_empty());
+    assert!(script.contains("rcli"));
+
+    assert!(!rcli(&["completion", "tcsh"]).status.success());
+}
+
+#[test]
+fn hash_check_exits_non_zero_on_mismatch() {
+    let output = rcli(&["hash", "-i", "fixtures/b64.txt", "--algo", "blake3"]);
+    assert!(output.status.success());
+    let digest = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    assert_eq!(digest.len(), 64);
+
+    let output = rcli(&[
+        "hash",
+        "-i",
+        "fixtures/b64.txt",
+        "--algo",
+        "blake3",
+        "--check",
+        &digest,
+    ]);
+    assert!(output.status.success());
+
+    let output = rcli(&["hash", "-i", "fixtures/b64.txt", "--check", &digest]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("checksum mismatch"));
+}
+
+#[test]
+fn text_verify_sets_exit_code() {
+    let output = rcli(&[
+        "text",
+        "sign",
+        "-i",
+        "Cargo.toml",
+        "-k",
+        "fixtures/blake3.key",
+    ]);
+    assert!(output.status.success());
+    let sig = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let verify = |input: &str, sig: &str, quiet: bool| {
+        // url-safe signatures may start with "-", so pass them as --sig=...
+        let sig = format!("--sig={}", sig);
+        let mut args = vec![
+            "text",
+            "verify",
+            "-i",
+            input,
+            "-k",
+            "fixtures/blake3.key",
+            // a symmetric tag is only trusted when --format names it
+            "--format",
+            "blake3",
+            &sig,
+        ];
+        if quiet {
+            args.push("--quiet");
+        }
+        rcli(&args)
+    };
+
+    let output = verify("Cargo.toml", &sig, false);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "valid\n");
+
+    let output = verify("fixtures/b64.txt", &sig, false);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "invalid\n");
+
+    let output = verify("fixtures/b64.txt", &sig, true);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+
+    let output = verify("Cargo.toml", "not base64!", false);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn genpass_writes_and_appends_to_output_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("passwords.txt");
+    let path = path.to_str().unwrap();
+
+    let output = rcli(&["genpass", "-c", "3", "-l", "20", "-o", path]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.matches("Password strength").count(), 3);
+
+    let content = std::fs::read_to_string(path).unwrap();
+    let lines: Vec<_> = content.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines.iter().all(|l| l.len() == 20));
+
+    let output = rcli(&["genpass", "-c", "2", "-o", path, "--append"]);
+    assert!(output.status.success());
+    let appended = std::fs::read_to_string(path).unwrap();
+    assert!(appended.starts_with(&content));
+    assert_eq!(appended.lines().count(), 5);
+
+    // without --append the file is only replaced with --force
+    let output = rcli(&["genpass", "-o", path]);
+    assert!(!output.status.success());
+    assert_eq!(std::fs::read_to_string(path).unwrap().lines().count(), 5);
+    let output = rcli(&["genpass", "-o", path, "--force"]);
+    assert!(output.status.success());
+    assert_eq!(std::fs::read_to_string(path).unwrap().lines().count(), 1);
+}
+
+#[test]
+fn text_verify_json_output() {
+    let output = rcli(&[
+        "text",
+        "sign",
+        "-i",
+        "Cargo.toml",
+        "-k",
+        "fixtures/blake3.key",
+    ]);
+    let sig = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let sig_arg = format!("--sig={}", sig);
+    let args = [
+        "text",
+        "verify",
+        "-k",
+        "fixtures/blake3.key",
+        "--format",
+        "blake3",
+        &sig_arg,
+        "--json",
+    ];
+    for (input, verified) in [("Cargo.toml", true), ("fixtures/b64.txt", false)] {
+        let output = rcli(&[&args[..], &["-i", input]].concat());
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(report["verified"], verified);
+        assert_eq!(report["format"], "blake3");
+        assert_eq!(report["input"], input);
+        assert_eq!(output.status.success(), verified);
+    }
+}
+
+#[test]
+fn jwt_verify_requires_alg() {
+    let output = rcli(&[
+        "jwt",
+        "sign",
+        "-k",
+        "fixtures/ed25519.pk",
+        "--sub",
+        "mallory",
+    ]);
+    assert!(output.status.success());
+    let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let output = rcli(&["jwt", "verify", "-t", &token, "-k", "fixtures/ed25519.pk"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--alg"));
+
+    let verify = ["jwt", "verify", "-t", &token, "-k", "fixtures/ed25519.pk"];
+    let output = rcli(&[&verify[..], &["--alg", "eddsa"]].concat());
+    assert!(!output.status.success());
+}